@@ -1,6 +1,9 @@
 package app.tauri.iap
 
 import android.app.Activity
+import android.content.ActivityNotFoundException
+import android.content.Intent
+import android.net.Uri
 import android.webkit.WebView
 import app.tauri.Logger
 import app.tauri.annotation.Command
@@ -15,6 +18,10 @@ import kotlinx.coroutines.Dispatchers
 import kotlinx.coroutines.launch
 import kotlinx.coroutines.withContext
 import org.json.JSONArray
+import org.json.JSONException
+import org.json.JSONObject
+import java.util.Locale
+import java.util.MissingResourceException
 
 @InvokeArg
 class GetProductsArgs {
@@ -55,11 +62,22 @@ class GetProductStatusArgs {
     var productType: String = "subs" // "subs" or "inapp"
 }
 
+@InvokeArg
+class OpenUrlArgs {
+    var url: String = ""
+}
+
 @TauriPlugin
 class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpdatedListener, BillingClientStateListener {
     private lateinit var billingClient: BillingClient
     private val coroutineScope = CoroutineScope(Dispatchers.Main)
     private var pendingPurchaseInvoke: Invoke? = null
+    private var lastCountryCode: String? = null
+    private var awaitingRedemption = false
+    // Purchase tokens owned before the redeem page was opened, null if they could not be queried
+    private var tokensBeforeRedemption: Set<String>? = null
+    // Pending purchases by purchase token, resolved through onPurchasesUpdated
+    private val pendingPurchases = mutableMapOf<String, JSObject>()
     private val TAG = "IapPlugin"
     
     // Keep in sync with PurchaseState in guest-js/index.ts
@@ -85,6 +103,25 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
         initializeBillingClient()
     }
     
+    override fun onResume() {
+        super.onResume()
+        if (awaitingRedemption) {
+            awaitingRedemption = false
+            val knownTokens = tokensBeforeRedemption
+            tokensBeforeRedemption = null
+            if (billingClient.isReady) {
+                emitRedeemedPurchases(knownTokens)
+            } else {
+                Logger.warn(TAG, "Billing client not ready, skipping redeemed purchase check")
+            }
+        }
+        if (pendingPurchases.isNotEmpty() && billingClient.isReady) {
+            queryAllPurchases { purchases ->
+                purchases?.let { reconcilePendingPurchases(it) }
+            }
+        }
+    }
+    
     private fun initializeBillingClient() {
         var params = PendingPurchasesParams.newBuilder()
             .enableOneTimeProducts()
@@ -261,20 +298,7 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
         
         billingClient.queryPurchasesAsync(params) { billingResult, purchases ->
             if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
-                val purchasesArray = purchases.map { purchase ->
-                    JSObject().apply {
-                        put("orderId", purchase.orderId)
-                        put("packageName", purchase.packageName)
-                        put("productId", purchase.products.firstOrNull() ?: "")
-                        put("purchaseTime", purchase.purchaseTime)
-                        put("purchaseToken", purchase.purchaseToken)
-                        put("purchaseState", translatePurchaseState(purchase.purchaseState))
-                        put("isAutoRenewing", purchase.isAutoRenewing)
-                        put("isAcknowledged", purchase.isAcknowledged)
-                        put("originalJson", purchase.originalJson)
-                        put("signature", purchase.signature)
-                    }
-                }
+                val purchasesArray = purchases.map { createPurchaseObject(it) }
 
                 val result = JSObject()
                 result.put("purchases", JSONArray(purchasesArray))
@@ -375,6 +399,7 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                         put("isAutoRenewing", productPurchase.isAutoRenewing)
                         put("isAcknowledged", productPurchase.isAcknowledged)
                         put("purchaseToken", productPurchase.purchaseToken)
+                        put("ownershipType", "purchased")  // No Family Sharing on Play
                         
                         // Note: Android doesn't provide expiration time directly for subscriptions
                         // It would require additional Google Play Developer API calls
@@ -390,6 +415,183 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
         }
     }
     
+    @Command
+    fun getStorefront(invoke: Invoke) {
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+        
+        val params = GetBillingConfigParams.newBuilder().build()
+        
+        billingClient.getBillingConfigAsync(params) { billingResult, billingConfig ->
+            if (billingResult.responseCode == BillingClient.BillingResponseCode.OK && billingConfig != null) {
+                // Play reports ISO 3166-1 alpha-2, StoreKit alpha-3; use alpha-3 everywhere
+                val countryCode = try {
+                    Locale("", billingConfig.countryCode).isO3Country
+                } catch (e: MissingResourceException) {
+                    invoke.reject("Unknown country code: ${billingConfig.countryCode}")
+                    return@getBillingConfigAsync
+                }
+                
+                // Play Billing doesn't expose a storefront id or currency
+                val storefront = JSObject().apply {
+                    put("countryCode", countryCode)
+                }
+                invoke.resolve(storefront)
+                
+                // Play has no storefront change notification, so report
+                // changes observed between calls
+                val previousCountryCode = lastCountryCode
+                lastCountryCode = countryCode
+                if (previousCountryCode != null && previousCountryCode != countryCode) {
+                    trigger("storefrontChanged", storefront)
+                }
+            } else {
+                invoke.reject("Failed to get storefront: ${billingResult.debugMessage}")
+            }
+        }
+    }
+    
+    @Command
+    fun canMakePayments(invoke: Invoke) {
+        // Play has no payment restriction check; purchases need a connected billing client
+        invoke.resolve(JSObject().put("canMakePayments", billingClient.isReady))
+    }
+    
+    @Command
+    fun openUrl(invoke: Invoke) {
+        val args = invoke.parseArgs(OpenUrlArgs::class.java)
+        
+        if (startViewActivity(args.url)) {
+            invoke.resolve(JSObject())
+        } else {
+            invoke.reject("No app can open ${args.url}")
+        }
+    }
+    
+    @Command
+    fun presentCodeRedemption(invoke: Invoke) {
+        val args = invoke.parseArgs(OpenUrlArgs::class.java)
+        
+        val openRedeemPage = { knownTokens: Set<String>? ->
+            if (startViewActivity(args.url)) {
+                // Check for the redeemed purchase once the user returns to the app
+                tokensBeforeRedemption = knownTokens
+                awaitingRedemption = true
+                invoke.resolve(JSObject())
+            } else {
+                invoke.reject("No app can open ${args.url}")
+            }
+        }
+        
+        if (!billingClient.isReady) {
+            openRedeemPage(null)
+            return
+        }
+        queryAllPurchases { purchases ->
+            if (purchases == null) {
+                Logger.warn(TAG, "Failed to query purchases before code redemption")
+            }
+            openRedeemPage(purchases?.map { it.purchaseToken }?.toSet())
+        }
+    }
+    
+    private fun startViewActivity(url: String): Boolean {
+        return try {
+            activity.startActivity(Intent(Intent.ACTION_VIEW, Uri.parse(url)))
+            true
+        } catch (e: ActivityNotFoundException) {
+            false
+        }
+    }
+    
+    @Command
+    fun getPendingPurchases(invoke: Invoke) {
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+        
+        queryAllPurchases { purchases ->
+            if (purchases == null) {
+                invoke.reject("Failed to query pending purchases")
+                return@queryAllPurchases
+            }
+            
+            // Pending purchases made before the app was restarted
+            purchases.filter { it.purchaseState == Purchase.PurchaseState.PENDING }.forEach { purchase ->
+                if (!pendingPurchases.containsKey(purchase.purchaseToken)) {
+                    trackPendingPurchase(purchase, purchase.purchaseTime)
+                }
+            }
+            reconcilePendingPurchases(purchases)
+            
+            val result = JSObject()
+            result.put("pendingPurchases", JSONArray(pendingPurchases.values.toList()))
+            invoke.resolve(result)
+        }
+    }
+    
+    // Codes redeemed in the Play Store arrive as new, unacknowledged purchases. Only
+    // tokens missing from knownTokens are emitted. Without a snapshot (knownTokens
+    // is null) every unacknowledged purchase is emitted, which may re-report
+    // purchases that predate the redemption and the app has not acknowledged yet
+    private fun emitRedeemedPurchases(knownTokens: Set<String>?) {
+        queryAllPurchases { purchases ->
+            if (purchases == null) {
+                Logger.warn(TAG, "Failed to query redeemed purchases")
+                return@queryAllPurchases
+            }
+            purchases
+                .filter { !it.isAcknowledged && knownTokens?.contains(it.purchaseToken) != true }
+                .forEach { purchase -> handlePurchase(purchase, resolvePending = false) }
+        }
+    }
+    
+    // Queries in-app products and subscriptions, passing null if either query fails
+    private fun queryAllPurchases(callback: (List<Purchase>?) -> Unit) {
+        val inAppParams = QueryPurchasesParams.newBuilder()
+            .setProductType(BillingClient.ProductType.INAPP)
+            .build()
+        val subsParams = QueryPurchasesParams.newBuilder()
+            .setProductType(BillingClient.ProductType.SUBS)
+            .build()
+        
+        billingClient.queryPurchasesAsync(inAppParams) { inAppResult, inAppPurchases ->
+            if (inAppResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                callback(null)
+                return@queryPurchasesAsync
+            }
+            billingClient.queryPurchasesAsync(subsParams) { subsResult, subsPurchases ->
+                if (subsResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                    callback(null)
+                } else {
+                    callback(inAppPurchases + subsPurchases)
+                }
+            }
+        }
+    }
+    
+    private fun trackPendingPurchase(purchase: Purchase, pendingSince: Long) {
+        pendingPurchases[purchase.purchaseToken] = JSObject().apply {
+            put("productId", purchase.products.firstOrNull() ?: "")
+            purchase.accountIdentifiers?.obfuscatedAccountId?.let { put("accountToken", it) }
+            put("purchaseToken", purchase.purchaseToken)
+            put("pendingSince", pendingSince)
+        }
+    }
+    
+    // Play drops declined or expired pending purchases without an update, so any
+    // tracked purchase missing from the query was declined
+    private fun reconcilePendingPurchases(purchases: List<Purchase>) {
+        val tokens = purchases.map { it.purchaseToken }.toSet()
+        val declined = pendingPurchases.keys.filter { it !in tokens }
+        for (token in declined) {
+            pendingPurchases.remove(token)?.let { trigger("pendingPurchaseDeclined", it) }
+        }
+    }
+    
     override fun onPurchasesUpdated(billingResult: BillingResult, purchases: List<Purchase>?) {
         when (billingResult.responseCode) {
             BillingClient.BillingResponseCode.OK -> {
@@ -410,29 +612,61 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
         }
     }
     
-    private fun handlePurchase(purchase: Purchase) {
-        if (purchase.purchaseState == Purchase.PurchaseState.PURCHASED) {
-            val purchaseData = JSObject().apply {
-                put("orderId", purchase.orderId)
-                put("packageName", purchase.packageName)
-                put("productId", purchase.products.firstOrNull() ?: "")
-                put("purchaseTime", purchase.purchaseTime)
-                put("purchaseToken", purchase.purchaseToken)
-                put("purchaseState", translatePurchaseState(purchase.purchaseState))
-                put("isAutoRenewing", purchase.isAutoRenewing)
-                put("isAcknowledged", purchase.isAcknowledged)
-                put("originalJson", purchase.originalJson)
-                put("signature", purchase.signature)
-            }
+    private fun handlePurchase(purchase: Purchase, resolvePending: Boolean = true) {
+        when (purchase.purchaseState) {
+            Purchase.PurchaseState.PURCHASED -> {
+                val purchaseData = createPurchaseObject(purchase)
 
-            pendingPurchaseInvoke?.resolve(purchaseData)
-            pendingPurchaseInvoke = null
-            
-            // Emit event for purchase state change
-            trigger("purchaseUpdated", purchaseData)
+                if (resolvePending) {
+                    pendingPurchaseInvoke?.resolve(purchaseData)
+                    pendingPurchaseInvoke = null
+                }
+                
+                // Emit event for purchase state change
+                trigger("purchaseUpdated", purchaseData)
+                
+                if (pendingPurchases.remove(purchase.purchaseToken) != null) {
+                    trigger("pendingPurchaseCompleted", purchaseData)
+                }
+            }
+            Purchase.PurchaseState.PENDING -> {
+                // Completed later through onPurchasesUpdated
+                if (!pendingPurchases.containsKey(purchase.purchaseToken)) {
+                    trackPendingPurchase(purchase, System.currentTimeMillis())
+                }
+                
+                if (resolvePending) {
+                    pendingPurchaseInvoke?.resolve(createPurchaseObject(purchase))
+                    pendingPurchaseInvoke = null
+                }
+            }
         }
     }
     
+    private fun createPurchaseObject(purchase: Purchase): JSObject = JSObject().apply {
+        put("orderId", purchase.orderId)
+        put("packageName", purchase.packageName)
+        put("productId", purchase.products.firstOrNull() ?: "")
+        put("purchaseTime", purchase.purchaseTime)
+        put("purchaseToken", purchase.purchaseToken)
+        put("purchaseState", translatePurchaseState(purchase.purchaseState))
+        put("isAutoRenewing", purchase.isAutoRenewing)
+        put("isAcknowledged", purchase.isAcknowledged)
+        put("originalJson", purchase.originalJson)
+        put("signature", purchase.signature)
+        purchase.accountIdentifiers?.obfuscatedAccountId?.let { put("accountToken", it) }
+        put("environment", purchaseEnvironment(purchase))
+        purchase.orderId?.let { put("transactionId", it) }
+        put("quantity", purchase.quantity)
+    }
+    
+    // Play marks license tester purchases with purchaseType 0 in the purchase JSON
+    private fun purchaseEnvironment(purchase: Purchase): String = try {
+        if (JSONObject(purchase.originalJson).optInt("purchaseType", -1) == 0) "sandbox" else "production"
+    } catch (e: JSONException) {
+        "production"
+    }
+    
     override fun onBillingSetupFinished(billingResult: BillingResult) {
         if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
             Logger.info(TAG, "Billing setup finished successfully")
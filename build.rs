@@ -11,6 +11,16 @@ const COMMANDS: &[&str] = &[
     "get_purchase_history",
     "acknowledge_purchase",
     "get_product_status",
+    "compare_prices",
+    "get_storefront",
+    "get_capabilities",
+    "get_consumable_balance_remaining",
+    "get_app_license",
+    "get_app_transaction",
+    "begin_refund_request",
+    "show_manage_subscriptions",
+    "present_code_redemption",
+    "get_pending_purchases",
 ];
 
 fn main() {
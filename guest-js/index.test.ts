@@ -6,16 +6,40 @@ import {
   restorePurchases,
   getPurchaseHistory,
   acknowledgePurchase,
+  consumePurchase,
+  getConsumableBalanceRemaining,
   getProductStatus,
+  comparePrices,
+  getStorefront,
+  getCapabilities,
+  getAppLicense,
+  getAppTransaction,
+  beginRefundRequest,
+  showManageSubscriptions,
+  presentCodeRedemption,
+  getPendingPurchases,
   onPurchaseUpdated,
+  onPurchaseRevoked,
+  onPendingPurchaseCompleted,
+  onPendingPurchaseDeclined,
+  onStorefrontChanged,
   PurchaseState,
   type GetProductsResponse,
   type Purchase,
   type RestorePurchasesResponse,
   type GetPurchaseHistoryResponse,
   type AcknowledgePurchaseResponse,
+  type ConsumePurchaseResponse,
+  type ConsumableBalance,
   type ProductStatus,
   type PurchaseOptions,
+  type ComparePricesResponse,
+  type Storefront,
+  type Capabilities,
+  type AppLicense,
+  type AppTransaction,
+  type RefundRequestResponse,
+  type GetPendingPurchasesResponse,
 } from "./index";
 
 // Mock Tauri API
@@ -60,6 +84,7 @@ describe("IAP Plugin", () => {
             priceAmountMicros: 9990000,
           },
         ],
+        invalidProductIds: [],
       };
       vi.mocked(invoke).mockResolvedValue(mockProducts);
 
@@ -75,7 +100,10 @@ describe("IAP Plugin", () => {
     });
 
     it("should default to subs product type", async () => {
-      const mockProducts: GetProductsResponse = { products: [] };
+      const mockProducts: GetProductsResponse = {
+        products: [],
+        invalidProductIds: [],
+      };
       vi.mocked(invoke).mockResolvedValue(mockProducts);
 
       await getProducts(["com.example.product"]);
@@ -99,6 +127,7 @@ describe("IAP Plugin", () => {
             formattedPrice: "$0.99",
           },
         ],
+        invalidProductIds: [],
       };
       vi.mocked(invoke).mockResolvedValue(mockProducts);
 
@@ -108,13 +137,63 @@ describe("IAP Plugin", () => {
     });
 
     it("should handle empty product list", async () => {
-      const mockProducts: GetProductsResponse = { products: [] };
+      const mockProducts: GetProductsResponse = {
+        products: [],
+        invalidProductIds: [],
+      };
       vi.mocked(invoke).mockResolvedValue(mockProducts);
 
       const result = await getProducts([]);
 
       expect(result.products).toHaveLength(0);
     });
+
+    it("should report product ids that were not found", async () => {
+      const mockProducts: GetProductsResponse = {
+        products: [],
+        invalidProductIds: ["com.example.typo"],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockProducts);
+
+      const result = await getProducts(["com.example.typo"], "inapp");
+
+      expect(result.invalidProductIds).toEqual(["com.example.typo"]);
+    });
+
+    it("should pass strict option", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("Products not found: com.example.typo"),
+      );
+
+      await expect(
+        getProducts(["com.example.typo"], "inapp", { strict: true }),
+      ).rejects.toThrow("Products not found");
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_products", {
+        payload: {
+          productIds: ["com.example.typo"],
+          productType: "inapp",
+          strict: true,
+        },
+      });
+    });
+
+    it("should pass refresh option to bypass the cache", async () => {
+      const mockProducts: GetProductsResponse = {
+        products: [],
+        invalidProductIds: [],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockProducts);
+
+      await getProducts(["com.example.product"], "inapp", { refresh: true });
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_products", {
+        payload: {
+          productIds: ["com.example.product"],
+          productType: "inapp",
+          refresh: true,
+        },
+      });
+    });
   });
 
   describe("purchase", () => {
@@ -256,6 +335,20 @@ describe("IAP Plugin", () => {
       expect(result).toEqual(mockResponse);
     });
 
+    it("should pass the ownership type filter", async () => {
+      const mockResponse: RestorePurchasesResponse = { purchases: [] };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      await restorePurchases("inapp", "purchased");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|restore_purchases", {
+        payload: {
+          productType: "inapp",
+          ownershipType: "purchased",
+        },
+      });
+    });
+
     it("should default to subs product type", async () => {
       const mockResponse: RestorePurchasesResponse = { purchases: [] };
       vi.mocked(invoke).mockResolvedValue(mockResponse);
@@ -336,6 +429,63 @@ describe("IAP Plugin", () => {
     });
   });
 
+  describe("consumePurchase", () => {
+    it("should consume purchase with token", async () => {
+      const mockResponse: ConsumePurchaseResponse = { success: true };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await consumePurchase("TOKEN123");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|consume_purchase", {
+        payload: {
+          purchaseToken: "TOKEN123",
+          quantity: undefined,
+        },
+      });
+      expect(result).toEqual(mockResponse);
+    });
+
+    it("should pass quantity and return remaining balance", async () => {
+      const mockResponse: ConsumePurchaseResponse = {
+        success: true,
+        balanceRemaining: 7,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await consumePurchase("9NBLGGH4TNNR/0010", 3);
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|consume_purchase", {
+        payload: {
+          purchaseToken: "9NBLGGH4TNNR/0010",
+          quantity: 3,
+        },
+      });
+      expect(result.balanceRemaining).toBe(7);
+    });
+  });
+
+  describe("getConsumableBalanceRemaining", () => {
+    it("should get balance with product id", async () => {
+      const mockResponse: ConsumableBalance = {
+        productId: "9NBLGGH4TNNR",
+        balanceRemaining: 10,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getConsumableBalanceRemaining("9NBLGGH4TNNR");
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_consumable_balance_remaining",
+        {
+          payload: {
+            productId: "9NBLGGH4TNNR",
+          },
+        },
+      );
+      expect(result).toEqual(mockResponse);
+    });
+  });
+
   describe("getProductStatus", () => {
     it("should get product status with correct parameters", async () => {
       const mockStatus: ProductStatus = {
@@ -409,6 +559,335 @@ describe("IAP Plugin", () => {
     });
   });
 
+  describe("comparePrices", () => {
+    it("should compare prices with correct parameters", async () => {
+      const products: GetProductsResponse["products"] = [
+        {
+          productId: "yearly",
+          title: "Yearly",
+          description: "Yearly plan",
+          productType: "subs",
+          subscriptionOfferDetails: [
+            {
+              offerToken: "yearly_token",
+              basePlanId: "yearly",
+              pricingPhases: [
+                {
+                  formattedPrice: "$49.99",
+                  priceCurrencyCode: "USD",
+                  priceAmountMicros: 49990000,
+                  billingPeriod: "P1Y",
+                  billingCycleCount: 0,
+                  recurrenceMode: 1,
+                },
+              ],
+            },
+          ],
+        },
+      ];
+      const mockResponse: ComparePricesResponse = {
+        comparisons: [
+          {
+            productId: "yearly",
+            basePlanId: "yearly",
+            offerToken: "yearly_token",
+            billingPeriod: "P1Y",
+            priceAmountMicros: 49990000,
+            priceCurrencyCode: "USD",
+            formattedPrice: "$49.99",
+            pricePerWeek: {
+              priceAmountMicros: 961346,
+              priceCurrencyCode: "USD",
+              formattedPrice: "$0.96",
+            },
+            pricePerMonth: {
+              priceAmountMicros: 4165833,
+              priceCurrencyCode: "USD",
+              formattedPrice: "$4.17",
+            },
+            pricePerYear: {
+              priceAmountMicros: 49990000,
+              priceCurrencyCode: "USD",
+              formattedPrice: "$49.99",
+            },
+            savingsPercent: 40,
+          },
+        ],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await comparePrices(products, "monthly");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|compare_prices", {
+        payload: {
+          products,
+          referenceProductId: "monthly",
+        },
+      });
+      expect(result.comparisons[0].pricePerMonth.formattedPrice).toBe("$4.17");
+      expect(result.comparisons[0].savingsPercent).toBe(40);
+    });
+  });
+
+  describe("getStorefront", () => {
+    it("should get storefront", async () => {
+      const mockResponse: Storefront = {
+        countryCode: "USA",
+        id: "143441",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getStorefront();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_storefront");
+      expect(result).toEqual(mockResponse);
+    });
+  });
+
+  describe("getCapabilities", () => {
+    it("should get capabilities", async () => {
+      const mockResponse: Capabilities = {
+        backend: "appStore",
+        supportedOperations: [],
+        prerequisitesMet: false,
+        prerequisiteError: "IAP requires the app to run from a .app bundle.",
+        canMakePayments: false,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getCapabilities();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_capabilities");
+      expect(result).toEqual(mockResponse);
+    });
+  });
+
+  describe("getAppLicense", () => {
+    it("should get app license", async () => {
+      const mockResponse: AppLicense = {
+        isApplicable: true,
+        isActive: true,
+        isTrial: true,
+        trialTimeRemainingSecs: 86400,
+        trialUniqueId: "trial-123",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getAppLicense();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_app_license");
+      expect(result).toEqual(mockResponse);
+    });
+
+    it("should return the not-applicable marker", async () => {
+      vi.mocked(invoke).mockResolvedValue({ isApplicable: false });
+
+      const result = await getAppLicense();
+
+      expect(result.isApplicable).toBe(false);
+      expect(result.isTrial).toBeUndefined();
+    });
+  });
+
+  describe("getAppTransaction", () => {
+    const mockResponse: AppTransaction = {
+      bundleId: "com.example.app",
+      appVersion: "2.0",
+      originalAppVersion: "1.0",
+      originalPurchaseTime: 1600000000000,
+      environment: "production",
+      signedTime: 1700000000000,
+      deviceVerification: "c2hhMzg0",
+      deviceVerificationNonce: "5D6E3F0A-1B2C-4D5E-8F90-A1B2C3D4E5F6",
+      jwsRepresentation: "header.payload.signature",
+    };
+
+    it("should get app transaction", async () => {
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getAppTransaction();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_app_transaction", {
+        payload: { refresh: false },
+      });
+      expect(result).toEqual(mockResponse);
+    });
+
+    it("should refresh app transaction", async () => {
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      await getAppTransaction(true);
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_app_transaction", {
+        payload: { refresh: true },
+      });
+    });
+  });
+
+  describe("onStorefrontChanged", () => {
+    it("should register storefrontChanged listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "storefrontChanged",
+        channelId: 1,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      const listener = await onStorefrontChanged(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "storefrontChanged",
+        callback,
+      );
+      expect(listener.unregister).toBe(mockUnregister);
+    });
+  });
+
+  describe("beginRefundRequest", () => {
+    it("should begin refund request", async () => {
+      const mockResponse: RefundRequestResponse = { status: "success" };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await beginRefundRequest("2000000123456789");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|begin_refund_request", {
+        payload: { transactionId: "2000000123456789" },
+      });
+      expect(result).toEqual(mockResponse);
+    });
+  });
+
+  describe("showManageSubscriptions", () => {
+    it("should open the subscription", async () => {
+      vi.mocked(invoke).mockResolvedValue(null);
+
+      await showManageSubscriptions("com.example.premium");
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|show_manage_subscriptions",
+        { payload: { productId: "com.example.premium" } },
+      );
+    });
+
+    it("should open all subscriptions without a product", async () => {
+      vi.mocked(invoke).mockResolvedValue(null);
+
+      await showManageSubscriptions();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|show_manage_subscriptions",
+        { payload: { productId: undefined } },
+      );
+    });
+  });
+
+  describe("presentCodeRedemption", () => {
+    it("should present code redemption", async () => {
+      vi.mocked(invoke).mockResolvedValue(null);
+
+      await presentCodeRedemption();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|present_code_redemption");
+    });
+  });
+
+  describe("getPendingPurchases", () => {
+    it("should get pending purchases", async () => {
+      const mockResponse: GetPendingPurchasesResponse = {
+        pendingPurchases: [
+          {
+            productId: "premium",
+            accountToken: "6f1c2a34-5b6d-4e7f-8a9b-0c1d2e3f4a5b",
+            pendingSince: 1700000000000,
+          },
+        ],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getPendingPurchases();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_pending_purchases");
+      expect(result).toEqual(mockResponse);
+    });
+  });
+
+  describe("onPendingPurchaseCompleted", () => {
+    it("should register pendingPurchaseCompleted listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "pendingPurchaseCompleted",
+        channelId: 1,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      const listener = await onPendingPurchaseCompleted(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "pendingPurchaseCompleted",
+        callback,
+      );
+      expect(listener.unregister).toBe(mockUnregister);
+    });
+  });
+
+  describe("onPendingPurchaseDeclined", () => {
+    it("should register pendingPurchaseDeclined listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "pendingPurchaseDeclined",
+        channelId: 1,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      const listener = await onPendingPurchaseDeclined(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "pendingPurchaseDeclined",
+        callback,
+      );
+      expect(listener.unregister).toBe(mockUnregister);
+    });
+  });
+
+  describe("onPurchaseRevoked", () => {
+    it("should register purchaseRevoked listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "purchaseRevoked",
+        channelId: 1,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      const listener = await onPurchaseRevoked(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "purchaseRevoked",
+        callback,
+      );
+      expect(listener.unregister).toBe(mockUnregister);
+    });
+  });
+
   describe("onPurchaseUpdated", () => {
     const createMockPluginListener = (unregister: () => Promise<void>) => ({
       plugin: "iap",
@@ -18,6 +18,7 @@ export interface PricingPhase {
   formattedPrice: string;
   priceCurrencyCode: string;
   priceAmountMicros: number;
+  /** ISO-8601 billing period with a single unit (e.g., "P1W", "P1M", "P1Y") */
   billingPeriod: string;
   billingCycleCount: number;
   recurrenceMode: number;
@@ -55,11 +56,62 @@ export interface Product {
   subscriptionOfferDetails?: SubscriptionOffer[];
 }
 
+/**
+ * Optional parameters for product requests
+ */
+export interface GetProductsOptions {
+  /** Bypass the product cache and query the store */
+  refresh?: boolean;
+  /** Reject if any requested product id is not found */
+  strict?: boolean;
+}
+
 /**
  * Response containing products fetched from the store
  */
 export interface GetProductsResponse {
   products: Product[];
+  /** Requested product ids the store did not return (typos, unpublished products) */
+  invalidProductIds: string[];
+}
+
+/**
+ * A price normalized to a fixed billing period
+ */
+export interface PeriodPrice {
+  /** Price in micros (price × 1,000,000) */
+  priceAmountMicros: number;
+  /** ISO 4217 currency code */
+  priceCurrencyCode: string;
+  /** Price formatted like the store's price for the same product */
+  formattedPrice: string;
+}
+
+/**
+ * Normalized pricing of a single subscription offer
+ */
+export interface PriceComparison {
+  productId: string;
+  basePlanId: string;
+  offerToken: string;
+  offerId?: string;
+  /** ISO-8601 billing period of the recurring phase (e.g., "P1Y") */
+  billingPeriod: string;
+  priceAmountMicros: number;
+  priceCurrencyCode: string;
+  formattedPrice: string;
+  pricePerWeek: PeriodPrice;
+  pricePerMonth: PeriodPrice;
+  pricePerYear: PeriodPrice;
+  /** Whole-percent savings per month against the reference offer, rounded down. Absent for the reference itself or when currencies differ. */
+  savingsPercent?: number;
+}
+
+/**
+ * Response containing price comparisons for subscription offers
+ */
+export interface ComparePricesResponse {
+  comparisons: PriceComparison[];
 }
 
 /**
@@ -72,8 +124,12 @@ export interface Purchase {
   packageName: string;
   /** Product identifier that was purchased */
   productId: string;
-  /** Unix timestamp (milliseconds) when the purchase was made */
-  purchaseTime: number;
+  /** Unix timestamp (milliseconds) when the purchase was made. Undefined on Windows if the Store does not report it. */
+  purchaseTime?: number;
+  /** Unix timestamp (milliseconds) when the entitlement expires. Undefined for entitlements that do not expire. (Windows only) */
+  expirationTime?: number;
+  /** Whether the entitlement is a trial. (Windows only) */
+  isTrial?: boolean;
   /** Token used to identify this purchase for acknowledgment and server-side verification */
   purchaseToken: string;
   /** Current state of the purchase. */
@@ -82,7 +138,7 @@ export interface Purchase {
   isAutoRenewing: boolean;
   /** Whether the purchase has been acknowledged. Unacknowledged purchases are refunded after 3 days. (Android only, always true on iOS/macOS) */
   isAcknowledged: boolean;
-  /** Raw JSON response from the store for server-side verification. (Android only) */
+  /** Raw JSON from the store: Play purchase data (Android), StoreKit transaction JSON (iOS/macOS) or the Windows receipt. Empty for pending purchases on iOS/macOS. */
   originalJson: string;
   /** Cryptographic signature for purchase verification. (Android only) */
   signature: string;
@@ -90,6 +146,62 @@ export interface Purchase {
   originalId?: string;
   /** JWS representation of the signed transaction for server-side validation. (iOS/macOS only) */
   jwsRepresentation?: string;
+  /** Unix timestamp (milliseconds) when the store refunded or revoked the purchase. (iOS/macOS only) */
+  revocationTime?: number;
+  /** Why the store revoked the purchase. (iOS/macOS only) */
+  revocationReason?: "developerIssue" | "other";
+  /** Account the purchase was made for: the `appAccountToken` on iOS/macOS, the `obfuscatedAccountId` on Android. */
+  accountToken?: string;
+  /** Whether the user bought the purchase or has it through Family Sharing. Always `purchased` outside iOS/macOS. */
+  ownershipType?: OwnershipType;
+  /** Store environment the purchase was made in. Server code should not count `sandbox` purchases as revenue. Always `production` on Windows, which cannot tell sandbox purchases apart. */
+  environment?: StoreEnvironment;
+  /** Store transaction ID (iOS/macOS) or order ID (Android); absent on Windows. Unique per subscription renewal. */
+  transactionId?: string;
+  /** Identifier of a subscription purchase event across devices. (iOS/macOS only) */
+  webOrderLineItemId?: string;
+  /** Number of units purchased. Defaults to 1. */
+  quantity?: number;
+}
+
+/**
+ * Store environment a purchase was made in. `xcode` is StoreKit testing in
+ * Xcode, `mock` a purchase made up by the app or its tests.
+ */
+export type StoreEnvironment = "production" | "sandbox" | "xcode" | "mock";
+
+/**
+ * How the user came to own a purchase: bought it, or shared by a family
+ * member through Family Sharing (iOS/macOS only)
+ */
+export type OwnershipType = "purchased" | "familyShared";
+
+/**
+ * A purchase awaiting approval, e.g. Ask to Buy, or a deferred payment
+ */
+export interface PendingPurchase {
+  productId: string;
+  /** Account the purchase was made for, if one was given */
+  accountToken?: string;
+  /** Play purchase token of the pending purchase. (Android only) */
+  purchaseToken?: string;
+  /** Unix timestamp (milliseconds) when the purchase became pending */
+  pendingSince: number;
+}
+
+/**
+ * Response containing the pending purchases
+ */
+export interface GetPendingPurchasesResponse {
+  pendingPurchases: PendingPurchase[];
+}
+
+/**
+ * Outcome of the refund sheet
+ */
+export interface RefundRequestResponse {
+  /** `success` means the request was submitted, not that it was granted */
+  status: "success" | "userCancelled";
 }
 
 /**
@@ -143,9 +255,87 @@ export interface ProductStatus {
   purchaseState?: PurchaseState;
   purchaseTime?: number;
   expirationTime?: number;
+  isTrial?: boolean;
   isAutoRenewing?: boolean;
   isAcknowledged?: boolean;
   purchaseToken?: string;
+  ownershipType?: OwnershipType;
+}
+
+/**
+ * The store region the user purchases from
+ */
+export interface Storefront {
+  /**
+   * ISO 3166-1 alpha-3 country code, e.g. "USA". On Windows, the Windows
+   * region setting if the app has no Store listing to take the market from.
+   */
+  countryCode: string;
+  /** Store-specific storefront identifier (App Store only) */
+  id?: string;
+  /** ISO 4217 currency code prices are charged in (Windows only) */
+  currencyCode?: string;
+}
+
+/**
+ * What the store backend can do in the current environment
+ */
+export interface Capabilities {
+  backend: "appStore" | "googlePlay" | "microsoftStore" | "unsupported";
+  /** Names of the functions in this module the backend supports */
+  supportedOperations: string[];
+  /** Whether runtime requirements are met, e.g. a signed .app bundle on macOS */
+  prerequisitesMet: boolean;
+  /** Why the prerequisites are not met */
+  prerequisiteError?: string;
+  /** Whether the user may make payments, e.g. not blocked by parental controls */
+  canMakePayments: boolean;
+}
+
+/**
+ * License of the app itself, as opposed to its in-app products
+ */
+export interface AppLicense {
+  /** Whether the store licenses the app. False on Android, where all other fields are undefined. */
+  isApplicable: boolean;
+  /** Whether the license is valid (iOS/macOS: the app transaction is verified) */
+  isActive?: boolean;
+  isTrial?: boolean;
+  /** Seconds left in the trial (Windows only) */
+  trialTimeRemainingSecs?: number;
+  /** Identifies the trial across reinstalls on the same account (Windows only) */
+  trialUniqueId?: string;
+  /** Unix timestamp (milliseconds) when the license expires, if it does */
+  expirationTime?: number;
+  /** App version the user originally purchased or downloaded (iOS/macOS only) */
+  originalAppVersion?: string;
+  /** Unix timestamp (milliseconds) of the original purchase or download (iOS/macOS only) */
+  originalPurchaseTime?: number;
+  /** Signed app transaction for server-side validation (iOS/macOS only) */
+  jwsRepresentation?: string;
+}
+
+/**
+ * The App Store's signed record of the user's purchase or download of the app
+ */
+export interface AppTransaction {
+  bundleId: string;
+  /** Version of the running app */
+  appVersion: string;
+  /** App version the user originally purchased or downloaded (CFBundleVersion on iOS, CFBundleShortVersionString on macOS) */
+  originalAppVersion: string;
+  /** Unix timestamp (milliseconds) of the original purchase or download */
+  originalPurchaseTime: number;
+  /** Unix timestamp (milliseconds) of the pre-order, if the user pre-ordered the app. Requires iOS 17.2 / macOS 14.2 */
+  preorderTime?: number;
+  environment: StoreEnvironment;
+  /** Unix timestamp (milliseconds) at which the App Store signed the transaction */
+  signedTime: number;
+  /** Base64 SHA-384 hash binding the transaction to this device */
+  deviceVerification: string;
+  deviceVerificationNonce: string;
+  /** Signed app transaction for server-side validation */
+  jwsRepresentation: string;
 }
 
 /**
@@ -175,8 +365,12 @@ export async function initialize(): Promise<InitializeResponse> {
 /**
  * Fetch product information from the app store.
  *
+ * Products are served from a cache for `productCacheTtlSecs` (plugin config);
+ * pass `{ refresh: true }` to query the store regardless.
+ *
  * @param productIds - Array of product identifiers to fetch
  * @param productType - Type of products: "subs" for subscriptions, "inapp" for one-time purchases
+ * @param options - Optional request parameters
  * @returns Promise resolving to product information
  * @example
  * ```typescript
@@ -184,16 +378,21 @@ export async function initialize(): Promise<InitializeResponse> {
  *   ['com.example.premium', 'com.example.remove_ads'],
  *   'inapp'
  * );
+ *
+ * // Bypass the product cache
+ * const fresh = await getProducts(['com.example.premium'], 'inapp', { refresh: true });
  * ```
  */
 export async function getProducts(
   productIds: string[],
   productType: "subs" | "inapp" = "subs",
+  options?: GetProductsOptions,
 ): Promise<GetProductsResponse> {
   return await invoke<GetProductsResponse>("plugin:iap|get_products", {
     payload: {
       productIds,
       productType,
+      ...options,
     },
   });
 }
@@ -241,6 +440,7 @@ export async function purchase(
  * Restore user's previous purchases.
  *
  * @param productType - Type of products to restore: "subs" or "inapp"
+ * @param ownershipType - Only restore purchases with this ownership type, e.g. to exclude Family Sharing
  * @returns Promise resolving to list of restored purchases
  * @example
  * ```typescript
@@ -248,16 +448,21 @@ export async function purchase(
  * purchases.forEach(purchase => {
  *   console.log(`Restored: ${purchase.productId}`);
  * });
+ *
+ * // Features only for the purchaser, not family members
+ * const { purchases: bought } = await restorePurchases('inapp', 'purchased');
  * ```
  */
 export async function restorePurchases(
   productType: "subs" | "inapp" = "subs",
+  ownershipType?: OwnershipType,
 ): Promise<RestorePurchasesResponse> {
   return await invoke<RestorePurchasesResponse>(
     "plugin:iap|restore_purchases",
     {
       payload: {
         productType,
+        ownershipType,
       },
     },
   );
@@ -315,6 +520,16 @@ export async function acknowledgePurchase(
  */
 export interface ConsumePurchaseResponse {
   success: boolean;
+  /** Units left after fulfillment (Windows Store-managed consumables only) */
+  balanceRemaining?: number;
+}
+
+/**
+ * Remaining units of a Store-managed consumable
+ */
+export interface ConsumableBalance {
+  productId: string;
+  balanceRemaining: number;
 }
 
 /**
@@ -324,10 +539,14 @@ export interface ConsumePurchaseResponse {
  * delivering the product to allow repeat purchases. On Android, if not called,
  * users will see "You already own this item" on subsequent purchase attempts.
  * 
- * On iOS/macOS/Windows, this is a no-op but safe to call for cross-platform code.
+ * On Windows, this reports the fulfillment to the Microsoft Store. Consuming the
+ * same purchase token again is a no-op, so failed calls can be retried safely.
+ *
+ * On iOS/macOS, this is a no-op but safe to call for cross-platform code.
  * For non-consumable products or subscriptions, use `acknowledgePurchase` instead.
  *
  * @param purchaseToken - Purchase token from the transaction
+ * @param quantity - Units to fulfill of a Store-managed consumable (Windows only), defaults to 1
  * @returns Promise resolving to consumption status
  * @example
  * ```typescript
@@ -338,12 +557,37 @@ export interface ConsumePurchaseResponse {
  */
 export async function consumePurchase(
   purchaseToken: string,
+  quantity?: number,
 ): Promise<ConsumePurchaseResponse> {
   return await invoke<ConsumePurchaseResponse>(
     "plugin:iap|consume_purchase",
     {
       payload: {
         purchaseToken,
+        quantity,
+      },
+    },
+  );
+}
+
+/**
+ * Get the remaining units of a Store-managed consumable (Windows only).
+ *
+ * @param productId - Store ID of the consumable add-on
+ * @returns Promise resolving to the remaining balance
+ * @example
+ * ```typescript
+ * const { balanceRemaining } = await getConsumableBalanceRemaining('9NBLGGH4TNNR');
+ * ```
+ */
+export async function getConsumableBalanceRemaining(
+  productId: string,
+): Promise<ConsumableBalance> {
+  return await invoke<ConsumableBalance>(
+    "plugin:iap|get_consumable_balance_remaining",
+    {
+      payload: {
+        productId,
       },
     },
   );
@@ -379,6 +623,207 @@ export async function getProductStatus(
   });
 }
 
+/**
+ * Get the store region the user purchases from, e.g. for tax copy or regional paywalls.
+ *
+ * @returns Promise resolving to the current storefront
+ * @example
+ * ```typescript
+ * const storefront = await getStorefront();
+ * if (storefront.countryCode === 'DEU') {
+ *   console.log('Prices include VAT');
+ * }
+ * ```
+ */
+export async function getStorefront(): Promise<Storefront> {
+  return await invoke<Storefront>("plugin:iap|get_storefront");
+}
+
+/**
+ * Check whether in-app purchases can be used before showing any store UI.
+ *
+ * Never fails because the store is unavailable; the reason is reported in
+ * `prerequisiteError` instead.
+ *
+ * @returns Promise resolving to the backend's capabilities
+ * @example
+ * ```typescript
+ * const capabilities = await getCapabilities();
+ * if (!capabilities.prerequisitesMet || !capabilities.canMakePayments) {
+ *   hideStore();
+ * }
+ * ```
+ */
+export async function getCapabilities(): Promise<Capabilities> {
+  return await invoke<Capabilities>("plugin:iap|get_capabilities");
+}
+
+/**
+ * Get the license of the app itself, e.g. to unlock a paid app after its trial.
+ *
+ * @returns Promise resolving to the app license
+ * @example
+ * ```typescript
+ * const license = await getAppLicense();
+ * if (license.isTrial) {
+ *   console.log(`Trial ends in ${license.trialTimeRemainingSecs} seconds`);
+ * }
+ * ```
+ */
+export async function getAppLicense(): Promise<AppLicense> {
+  return await invoke<AppLicense>("plugin:iap|get_app_license");
+}
+
+/**
+ * Get the App Store's record of the user's purchase or download of the app,
+ * e.g. to grandfather users who bought the app before it went free.
+ * Only available on iOS 16+ and macOS.
+ *
+ * @param refresh - Fetch a new app transaction from the App Store. May ask the user to sign in, so only pass `true` in response to a user action.
+ * @returns Promise resolving to the verified app transaction
+ * @example
+ * ```typescript
+ * const transaction = await getAppTransaction();
+ * if (compareVersions(transaction.originalAppVersion, '2.0') < 0) {
+ *   unlockPremium();
+ * }
+ * ```
+ */
+export async function getAppTransaction(
+  refresh: boolean = false,
+): Promise<AppTransaction> {
+  return await invoke<AppTransaction>("plugin:iap|get_app_transaction", {
+    payload: {
+      refresh,
+    },
+  });
+}
+
+/**
+ * Show the App Store refund sheet for a transaction.
+ * Only available on iOS and macOS.
+ *
+ * A submitted request is reviewed by Apple. If the refund is granted, the
+ * purchase is reported through `onPurchaseRevoked`.
+ *
+ * @param transactionId - The transaction to refund, i.e. the purchase's `purchaseToken`
+ * @returns Promise resolving to whether the user submitted the request
+ * @example
+ * ```typescript
+ * const { status } = await beginRefundRequest(purchase.purchaseToken);
+ * if (status === 'success') {
+ *   console.log('Refund requested');
+ * }
+ * ```
+ */
+export async function beginRefundRequest(
+  transactionId: string,
+): Promise<RefundRequestResponse> {
+  return await invoke<RefundRequestResponse>(
+    "plugin:iap|begin_refund_request",
+    {
+      payload: {
+        transactionId,
+      },
+    },
+  );
+}
+
+/**
+ * Open the store's page for managing, e.g. cancelling, subscriptions.
+ *
+ * iOS shows the StoreKit sheet, macOS opens the App Store subscriptions
+ * page, Android the Play subscription center and Windows the add-on's
+ * Microsoft Store page.
+ *
+ * @param productId - Subscription to open on Android and Windows. Without it all subscriptions are shown.
+ * @returns Promise resolving once the page is shown
+ * @example
+ * ```typescript
+ * await showManageSubscriptions('com.example.premium');
+ * ```
+ */
+export async function showManageSubscriptions(
+  productId?: string,
+): Promise<void> {
+  await invoke("plugin:iap|show_manage_subscriptions", {
+    payload: {
+      productId,
+    },
+  });
+}
+
+/**
+ * Open the store's UI for redeeming offer or promo codes.
+ *
+ * iOS and macOS show the App Store offer code sheet (macOS 15+), Android
+ * opens the Play Store redeem page. Redeemed codes are delivered to
+ * `onPurchaseUpdated` like any other purchase.
+ *
+ * @returns Promise resolving once the redemption UI is shown
+ * @example
+ * ```typescript
+ * await onPurchaseUpdated((purchase) => unlock(purchase.productId));
+ * await presentCodeRedemption();
+ * ```
+ */
+export async function presentCodeRedemption(): Promise<void> {
+  await invoke("plugin:iap|present_code_redemption");
+}
+
+/**
+ * Get the purchases that are still awaiting approval or payment.
+ *
+ * A purchase that needs Ask to Buy approval or a deferred payment resolves
+ * `purchase` with the `PENDING` state. Once the store completes or declines
+ * it, `onPendingPurchaseCompleted` or `onPendingPurchaseDeclined` fires.
+ * Windows has no pending purchases and always returns an empty list.
+ *
+ * @returns Promise resolving to the pending purchases
+ * @example
+ * ```typescript
+ * const { pendingPurchases } = await getPendingPurchases();
+ * if (pendingPurchases.some(p => p.productId === 'premium')) {
+ *   showAwaitingApproval();
+ * }
+ * ```
+ */
+export async function getPendingPurchases(): Promise<GetPendingPurchasesResponse> {
+  return await invoke<GetPendingPurchasesResponse>(
+    "plugin:iap|get_pending_purchases",
+  );
+}
+
+/**
+ * Compare the recurring prices of subscription offers, e.g. for a paywall.
+ *
+ * Every offer's recurring pricing phase is normalized to per-week, per-month and
+ * per-year prices, formatted in the product's currency. Savings are computed
+ * per month against the reference product.
+ *
+ * @param products - Products as returned by `getProducts`
+ * @param referenceProductId - Product to compute savings against. Defaults to the offer with the highest price per month.
+ * @returns Promise resolving to one comparison per subscription offer
+ * @example
+ * ```typescript
+ * const { products } = await getProducts(['monthly', 'yearly'], 'subs');
+ * const { comparisons } = await comparePrices(products, 'monthly');
+ * const yearly = comparisons.find(c => c.productId === 'yearly');
+ * console.log(`${yearly.pricePerMonth.formattedPrice}/month, save ${yearly.savingsPercent}%`);
+ * ```
+ */
+export async function comparePrices(
+  products: Product[],
+  referenceProductId?: string,
+): Promise<ComparePricesResponse> {
+  return await invoke<ComparePricesResponse>("plugin:iap|compare_prices", {
+    payload: {
+      products,
+      referenceProductId,
+    },
+  });
+}
+
 /**
  * Listen for purchase updates.
  * This event is triggered when a purchase state changes.
@@ -403,3 +848,85 @@ export async function onPurchaseUpdated(
 ): Promise<PluginListener> {
   return await addPluginListener("iap", "purchaseUpdated", callback);
 }
+
+/**
+ * Listen for storefront changes, e.g. when the user switches their store country.
+ *
+ * On iOS and macOS this is reported by StoreKit. Android and Windows have no
+ * change notification, so the event fires when `getStorefront` returns a
+ * different storefront than the previous call.
+ *
+ * @param callback - Function to call with the new storefront
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onStorefrontChanged((storefront) => {
+ *   console.log(`Storefront changed to ${storefront.countryCode}`);
+ * });
+ * ```
+ */
+export async function onStorefrontChanged(
+  callback: (storefront: Storefront) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "storefrontChanged", callback);
+}
+
+/**
+ * Listen for refunded or revoked purchases, so the app can withdraw the
+ * entitlement. Only reported on iOS and macOS.
+ *
+ * The purchase is also delivered to `onPurchaseUpdated` with the `CANCELED` state.
+ *
+ * @param callback - Function to call with the revoked purchase
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onPurchaseRevoked((purchase) => {
+ *   console.log(`Revoked at ${purchase.revocationTime}: ${purchase.productId}`);
+ * });
+ * ```
+ */
+export async function onPurchaseRevoked(
+  callback: (purchase: Purchase) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "purchaseRevoked", callback);
+}
+
+/**
+ * Listen for pending purchases the store has completed.
+ *
+ * The purchase is also delivered to `onPurchaseUpdated`.
+ *
+ * @param callback - Function to call with the completed purchase
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onPendingPurchaseCompleted((purchase) => {
+ *   unlock(purchase.productId);
+ * });
+ * ```
+ */
+export async function onPendingPurchaseCompleted(
+  callback: (purchase: Purchase) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "pendingPurchaseCompleted", callback);
+}
+
+/**
+ * Listen for pending purchases that were declined, e.g. an Ask to Buy request
+ * the parent rejected or a deferred payment that was never made.
+ *
+ * @param callback - Function to call with the declined pending purchase
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onPendingPurchaseDeclined((pending) => {
+ *   console.log(`Purchase of ${pending.productId} was declined`);
+ * });
+ * ```
+ */
+export async function onPendingPurchaseDeclined(
+  callback: (pending: PendingPurchase) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "pendingPurchaseDeclined", callback);
+}
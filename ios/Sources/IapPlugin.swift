@@ -34,6 +34,14 @@ class GetProductStatusArgs: Decodable {
     let productType: String?
 }
 
+class GetAppTransactionArgs: Decodable {
+    let refresh: Bool?
+}
+
+class BeginRefundRequestArgs: Decodable {
+    let transactionId: String
+}
+
 /// Keep in sync with PurchaseState in guest-js/index.ts
 enum PurchaseStateValue: Int {
     case purchased = 0
@@ -44,6 +52,13 @@ enum PurchaseStateValue: Int {
 @available(iOS 15.0, *)
 class IapPlugin: Plugin {
     private var updateListenerTask: Task<Void, Error>?
+    private var storefrontListenerTask: Task<Void, Error>?
+    /// Purchases awaiting Ask to Buy approval or a deferred payment, only
+    /// accessed on pendingQueue
+    private var pendingPurchases: [JsonObject] = []
+    private let pendingQueue = DispatchQueue(label: "app.tauri.iap.pendingPurchases")
+    /// Ask to Buy requests expire after 24 hours, without a transaction update
+    private let pendingRequestTimeoutMillis = 24 * 60 * 60 * 1000
     
     public override func load(webview: WKWebView) {
         super.load(webview: webview)
@@ -54,10 +69,18 @@ class IapPlugin: Plugin {
                 await self.handleTransactionUpdate(update)
             }
         }
+
+        // Start listening for storefront changes
+        storefrontListenerTask = Task {
+            for await storefront in Storefront.updates {
+                self.trigger("storefrontChanged", data: self.createStorefrontObject(from: storefront) as! JSObject)
+            }
+        }
     }
     
     deinit {
         updateListenerTask?.cancel()
+        storefrontListenerTask?.cancel()
     }
 
     @objc public func getProducts(_ invoke: Invoke) async throws {
@@ -78,6 +101,7 @@ class IapPlugin: Plugin {
                 // Add pricing information
                 productDict["formattedPrice"] = product.displayPrice
                 productDict["priceCurrencyCode"] = getCurrencyCode(for: product)
+                productDict["priceAmountMicros"] = priceMicros(product.price)
                 
                 // Handle subscription-specific information
                 if product.type == .autoRenewable || product.type == .nonRenewable {
@@ -93,7 +117,7 @@ class IapPlugin: Plugin {
                                 "pricingPhases": [[
                                     "formattedPrice": introOffer.displayPrice,
                                     "priceCurrencyCode": getCurrencyCode(for: product),
-                                    "priceAmountMicros": 0,  // Not available in StoreKit 2
+                                    "priceAmountMicros": priceMicros(introOffer.price),
                                     "billingPeriod": formatSubscriptionPeriod(introOffer.period),
                                     "billingCycleCount": introOffer.periodCount,
                                     "recurrenceMode": 0
@@ -110,7 +134,7 @@ class IapPlugin: Plugin {
                             "pricingPhases": [[
                                 "formattedPrice": product.displayPrice,
                                 "priceCurrencyCode": getCurrencyCode(for: product),
-                                "priceAmountMicros": 0,
+                                "priceAmountMicros": priceMicros(product.price),
                                 "billingPeriod": formatSubscriptionPeriod(subscription.subscriptionPeriod),
                                 "billingCycleCount": 0,
                                 "recurrenceMode": 1
@@ -120,9 +144,6 @@ class IapPlugin: Plugin {
                         
                         productDict["subscriptionOfferDetails"] = subscriptionOffers
                     }
-                } else {
-                    // One-time purchase
-                    productDict["priceAmountMicros"] = 0  // Not available in StoreKit 2
                 }
                 
                 productsArray.append(productDict)
@@ -179,7 +200,27 @@ class IapPlugin: Plugin {
                 invoke.reject("Purchase cancelled by user")
                 
             case .pending:
-                invoke.reject("Purchase is pending")
+                // Completed or declined later through Transaction.updates
+                var pending: JsonObject = [
+                    "productId": args.productId,
+                    "pendingSince": Int(Date().timeIntervalSince1970 * 1000)
+                ]
+                var purchase: JsonObject = [
+                    "packageName": Bundle.main.bundleIdentifier ?? "",
+                    "productId": args.productId,
+                    "purchaseToken": "",
+                    "purchaseState": PurchaseStateValue.pending.rawValue,
+                    "isAutoRenewing": false,
+                    "isAcknowledged": false,
+                    "originalJson": "",
+                    "signature": ""
+                ]
+                if let appAccountToken = args.appAccountToken {
+                    pending["accountToken"] = appAccountToken.lowercased()
+                    purchase["accountToken"] = appAccountToken.lowercased()
+                }
+                trackPendingPurchase(pending)
+                invoke.resolve(purchase)
                 
             @unknown default:
                 invoke.reject("Unknown purchase result")
@@ -289,6 +330,7 @@ class IapPlugin: Plugin {
                     statusResult["isOwned"] = true
                     statusResult["purchaseTime"] = Int(transaction.purchaseDate.timeIntervalSince1970 * 1000)
                     statusResult["purchaseToken"] = String(transaction.id)
+                    statusResult["ownershipType"] = ownershipType(of: transaction)
                     statusResult["isAcknowledged"] = true  // Always true on iOS
                     
                     // Check if expired/revoked
@@ -343,6 +385,170 @@ class IapPlugin: Plugin {
         invoke.resolve(statusResult)
     }
     
+    @objc public func getStorefront(_ invoke: Invoke) async throws {
+        guard let storefront = await Storefront.current else {
+            invoke.reject("Storefront not available")
+            return
+        }
+        invoke.resolve(createStorefrontObject(from: storefront))
+    }
+    
+    @objc public func canMakePayments(_ invoke: Invoke) throws {
+        invoke.resolve(["canMakePayments": AppStore.canMakePayments])
+    }
+    
+    @objc public func getPendingPurchases(_ invoke: Invoke) throws {
+        expirePendingPurchases()
+        let pending = pendingQueue.sync { pendingPurchases }
+        invoke.resolve(["pendingPurchases": pending])
+    }
+    
+    @objc public func getAppLicense(_ invoke: Invoke) async throws {
+        guard #available(iOS 16.0, *) else {
+            invoke.reject("App transactions require iOS 16.0 or later")
+            return
+        }
+        
+        do {
+            let result = try await AppTransaction.shared
+            invoke.resolve(createAppLicenseObject(from: result))
+        } catch {
+            invoke.reject("Failed to get app transaction: \(error.localizedDescription)")
+        }
+    }
+    
+    @objc public func showManageSubscriptions(_ invoke: Invoke) async throws {
+        guard let scene = await MainActor.run(body: {
+            UIApplication.shared.connectedScenes.first { $0.activationState == .foregroundActive } as? UIWindowScene
+        }) else {
+            invoke.reject("No window scene to present the subscriptions sheet from")
+            return
+        }
+        
+        do {
+            try await AppStore.showManageSubscriptions(in: scene)
+            invoke.resolve([:])
+        } catch {
+            invoke.reject("Failed to show subscriptions: \(error.localizedDescription)")
+        }
+    }
+    
+    @objc public func presentCodeRedemption(_ invoke: Invoke) async throws {
+        // Redeemed offer codes arrive through Transaction.updates as purchaseUpdated
+        guard #available(iOS 16.0, *) else {
+            await MainActor.run {
+                SKPaymentQueue.default().presentCodeRedemptionSheet()
+            }
+            invoke.resolve([:])
+            return
+        }
+        
+        guard let scene = await MainActor.run(body: {
+            UIApplication.shared.connectedScenes.first { $0.activationState == .foregroundActive } as? UIWindowScene
+        }) else {
+            invoke.reject("No window scene to present the redemption sheet from")
+            return
+        }
+        
+        do {
+            try await AppStore.presentOfferCodeRedeemSheet(in: scene)
+            invoke.resolve([:])
+        } catch {
+            invoke.reject("Failed to present redemption sheet: \(error.localizedDescription)")
+        }
+    }
+    
+    @objc public func beginRefundRequest(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(BeginRefundRequestArgs.self)
+        
+        guard let id = UInt64(args.transactionId) else {
+            invoke.reject("Invalid transaction ID")
+            return
+        }
+        guard let scene = await MainActor.run(body: {
+            UIApplication.shared.connectedScenes.first { $0.activationState == .foregroundActive } as? UIWindowScene
+        }) else {
+            invoke.reject("No window scene to present the refund sheet from")
+            return
+        }
+        
+        do {
+            let status = try await Transaction.beginRefundRequest(for: id, in: scene)
+            invoke.resolve(["status": status == .success ? "success" : "userCancelled"])
+        } catch {
+            invoke.reject("Refund request failed: \(error.localizedDescription)")
+        }
+    }
+    
+    @objc public func getAppTransaction(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetAppTransactionArgs.self)
+        
+        guard #available(iOS 16.0, *) else {
+            invoke.reject("App transactions require iOS 16.0 or later")
+            return
+        }
+        
+        do {
+            // Refreshing may prompt the user to sign in to the App Store
+            let result = args.refresh == true ? try await AppTransaction.refresh() : try await AppTransaction.shared
+            guard case .verified(let appTransaction) = result else {
+                invoke.reject("App transaction not verified")
+                return
+            }
+            invoke.resolve(createAppTransactionObject(from: appTransaction, jwsRepresentation: result.jwsRepresentation))
+        } catch {
+            invoke.reject("Failed to get app transaction: \(error.localizedDescription)")
+        }
+    }
+    
+    @available(iOS 16.0, *)
+    private func createAppTransactionObject(from appTransaction: AppTransaction, jwsRepresentation: String) -> JsonObject {
+        var object: JsonObject = [
+            "bundleId": appTransaction.bundleID,
+            "appVersion": appTransaction.appVersion,
+            "originalAppVersion": appTransaction.originalAppVersion,
+            "originalPurchaseTime": Int(appTransaction.originalPurchaseDate.timeIntervalSince1970 * 1000),
+            "environment": appTransaction.environment.rawValue.lowercased(),
+            "signedTime": Int(appTransaction.signedDate.timeIntervalSince1970 * 1000),
+            "deviceVerification": appTransaction.deviceVerification.base64EncodedString(),
+            "deviceVerificationNonce": appTransaction.deviceVerificationNonce.uuidString,
+            "jwsRepresentation": jwsRepresentation
+        ]
+        if #available(iOS 17.2, macOS 14.2, *), let preorderDate = appTransaction.preorderDate {
+            object["preorderTime"] = Int(preorderDate.timeIntervalSince1970 * 1000)
+        }
+        return object
+    }
+    
+    @available(iOS 16.0, *)
+    private func createAppLicenseObject(from result: VerificationResult<AppTransaction>) -> JsonObject {
+        let appTransaction = result.unsafePayloadValue
+        let isVerified: Bool
+        switch result {
+        case .verified:
+            isVerified = true
+        case .unverified:
+            isVerified = false
+        }
+        
+        return [
+            "isApplicable": true,
+            "isActive": isVerified,
+            "isTrial": false,  // The App Store has no app trials
+            "originalAppVersion": appTransaction.originalAppVersion,
+            "originalPurchaseTime": Int(appTransaction.originalPurchaseDate.timeIntervalSince1970 * 1000),
+            "jwsRepresentation": result.jwsRepresentation
+        ]
+    }
+    
+    private func createStorefrontObject(from storefront: Storefront) -> JsonObject {
+        // StoreKit doesn't expose the storefront currency
+        return [
+            "countryCode": storefront.countryCode,
+            "id": storefront.id
+        ]
+    }
+    
     private func handleTransactionUpdate(_ result: VerificationResult<Transaction>) async {
         switch result {
         case .verified(let transaction):
@@ -351,8 +557,13 @@ class IapPlugin: Plugin {
                 if let purchase = try? await createPurchaseObject(from: result, product: product) {
                     // Emit event - convert to JSObject-compatible format
                     trigger("purchaseUpdated", data: purchase as! JSObject)
+                    if transaction.revocationDate != nil {
+                        trigger("purchaseRevoked", data: purchase as! JSObject)
+                    }
+                    resolvePendingPurchase(purchase)
                 }
             }
+            expirePendingPurchases()
 
             // Always finish transactions
             await transaction.finish()
@@ -363,6 +574,61 @@ class IapPlugin: Plugin {
         }
     }
     
+    private func trackPendingPurchase(_ pending: JsonObject) {
+        pendingQueue.sync {
+            pendingPurchases.removeAll {
+                $0["productId"] as? String == pending["productId"] as? String
+                    && $0["accountToken"] as? String == pending["accountToken"] as? String
+            }
+            pendingPurchases.append(pending)
+        }
+    }
+    
+    /// Emits pendingPurchaseCompleted if the update completes a tracked pending
+    /// purchase. Pending purchases have no transaction, so a canceled update is
+    /// the revocation of an older one and declines nothing. Keep in sync with
+    /// src/pending.rs
+    private func resolvePendingPurchase(_ purchase: JsonObject) {
+        guard purchase["purchaseState"] as? Int == PurchaseStateValue.purchased.rawValue else {
+            return
+        }
+        let resolved: JsonObject? = pendingQueue.sync {
+            guard let index = pendingPurchases.firstIndex(where: { pending in
+                guard pending["productId"] as? String == purchase["productId"] as? String else {
+                    return false
+                }
+                guard let accountToken = pending["accountToken"] as? String else {
+                    return true
+                }
+                return accountToken == purchase["accountToken"] as? String
+            }) else {
+                return nil
+            }
+            return pendingPurchases.remove(at: index)
+        }
+        
+        if resolved != nil {
+            trigger("pendingPurchaseCompleted", data: purchase as! JSObject)
+        }
+    }
+    
+    /// Emits pendingPurchaseDeclined for Ask to Buy requests that expired, as
+    /// StoreKit sends no update when one is declined
+    private func expirePendingPurchases() {
+        let now = Int(Date().timeIntervalSince1970 * 1000)
+        let isExpired = { (pending: JsonObject) in
+            now - (pending["pendingSince"] as? Int ?? now) > self.pendingRequestTimeoutMillis
+        }
+        let expired: [JsonObject] = pendingQueue.sync {
+            let expired = pendingPurchases.filter(isExpired)
+            pendingPurchases.removeAll(where: isExpired)
+            return expired
+        }
+        for pending in expired {
+            trigger("pendingPurchaseDeclined", data: pending as! JSObject)
+        }
+    }
+    
     private func createPurchaseObject(from verificationResult: VerificationResult<Transaction>, product: Product) async throws -> JsonObject {
         guard case .verified(let transaction) = verificationResult else {
             throw NSError(domain: "IapPlugin", code: -1, userInfo: [NSLocalizedDescriptionKey: "Transaction not verified"])
@@ -383,7 +649,7 @@ class IapPlugin: Plugin {
             }
         }
 
-        return [
+        var purchase: JsonObject = [
             "orderId": String(transaction.id),
             "originalId": String(transaction.originalID),
             "jwsRepresentation": verificationResult.jwsRepresentation,
@@ -394,9 +660,36 @@ class IapPlugin: Plugin {
             "purchaseState": transaction.revocationDate == nil ? PurchaseStateValue.purchased.rawValue : PurchaseStateValue.canceled.rawValue,
             "isAutoRenewing": isAutoRenewing,
             "isAcknowledged": true,  // Always true on iOS
-            "originalJson": "",      // Not available in StoreKit 2
+            "originalJson": String(decoding: transaction.jsonRepresentation, as: UTF8.self),
             "signature": ""          // Not available in StoreKit 2
         ]
+        if let revocationDate = transaction.revocationDate {
+            purchase["revocationTime"] = Int(revocationDate.timeIntervalSince1970 * 1000)
+            purchase["revocationReason"] = transaction.revocationReason == .developerIssue ? "developerIssue" : "other"
+        }
+        if let appAccountToken = transaction.appAccountToken {
+            purchase["accountToken"] = appAccountToken.uuidString.lowercased()
+        }
+        purchase["ownershipType"] = ownershipType(of: transaction)
+        purchase["environment"] = environment(of: transaction)
+        purchase["transactionId"] = String(transaction.id)
+        purchase["quantity"] = transaction.purchasedQuantity
+        if let webOrderLineItemID = transaction.webOrderLineItemID {
+            purchase["webOrderLineItemId"] = webOrderLineItemID
+        }
+        return purchase
+    }
+
+    /// "production", "sandbox" or "xcode"
+    private func environment(of transaction: Transaction) -> String {
+        if #available(iOS 16.0, *) {
+            return transaction.environment.rawValue.lowercased()
+        }
+        return transaction.environmentStringRepresentation.lowercased()
+    }
+
+    private func ownershipType(of transaction: Transaction) -> String {
+        transaction.ownershipType == .familyShared ? "familyShared" : "purchased"
     }
     
     private func formatSubscriptionPeriod(_ period: Product.SubscriptionPeriod) -> String {
@@ -414,6 +707,14 @@ class IapPlugin: Plugin {
         }
     }
     
+    /// Price in micros (amount × 1,000,000), rounded to the nearest micro.
+    private func priceMicros(_ price: Decimal) -> Int64 {
+        var micros = price * 1_000_000
+        var rounded = Decimal()
+        NSDecimalRound(&rounded, &micros, 0, .plain)
+        return NSDecimalNumber(decimal: rounded).int64Value
+    }
+
     private func getCurrencyCode(for product: Product) -> String {
         if #available(iOS 16.0, *) {
             return product.priceFormatStyle.locale.currency?.identifier ?? ""
@@ -449,6 +750,30 @@ func initPlugin() -> Plugin {
             @objc func getProductStatus(_ invoke: Invoke) {
                 invoke.reject("IAP requires iOS 15.0 or later")
             }
+            @objc func getStorefront(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func canMakePayments(_ invoke: Invoke) {
+                invoke.resolve(["canMakePayments": false])
+            }
+            @objc func getAppLicense(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getAppTransaction(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func beginRefundRequest(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func showManageSubscriptions(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func presentCodeRedemption(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getPendingPurchases(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
         }
         return DummyPlugin()
     }
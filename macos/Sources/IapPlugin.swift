@@ -1,3 +1,4 @@
+import AppKit
 import StoreKit
 
 extension FFIResult: Error {}
@@ -13,6 +14,7 @@ enum PurchaseStateValue: Int {
 
 class IapPlugin {
     private var updateListenerTask: Task<Void, Error>?
+    private var storefrontListenerTask: Task<Void, Error>?
 
     init() {
         // Start listening for transaction updates
@@ -21,10 +23,20 @@ class IapPlugin {
                 await self.handleTransactionUpdate(update)
             }
         }
+
+        // Start listening for storefront changes
+        storefrontListenerTask = Task {
+            for await storefront in Storefront.updates {
+                if let jsonString = try? self.serializeToJSON(self.createStorefrontObject(from: storefront)) {
+                    try? trigger("storefrontChanged", jsonString)
+                }
+            }
+        }
     }
 
     deinit {
         updateListenerTask?.cancel()
+        storefrontListenerTask?.cancel()
     }
 
     public func getProducts(productIds: RustVec<RustString>, productType: RustString)
@@ -51,6 +63,7 @@ class IapPlugin {
             // Add pricing information
             productDict["formattedPrice"] = product.displayPrice
             productDict["priceCurrencyCode"] = getCurrencyCode(for: product)
+            productDict["priceAmountMicros"] = priceMicros(product.price)
 
             // Handle subscription-specific information
             if product.type == .autoRenewable || product.type == .nonRenewable {
@@ -67,7 +80,7 @@ class IapPlugin {
                                 [
                                     "formattedPrice": introOffer.displayPrice,
                                     "priceCurrencyCode": getCurrencyCode(for: product),
-                                    "priceAmountMicros": 0,  // Not available in StoreKit 2
+                                    "priceAmountMicros": priceMicros(introOffer.price),
                                     "billingPeriod": formatSubscriptionPeriod(introOffer.period),
                                     "billingCycleCount": introOffer.periodCount,
                                     "recurrenceMode": 0,
@@ -86,7 +99,7 @@ class IapPlugin {
                             [
                                 "formattedPrice": product.displayPrice,
                                 "priceCurrencyCode": getCurrencyCode(for: product),
-                                "priceAmountMicros": 0,
+                                "priceAmountMicros": priceMicros(product.price),
                                 "billingPeriod": formatSubscriptionPeriod(
                                     subscription.subscriptionPeriod),
                                 "billingCycleCount": 0,
@@ -98,9 +111,6 @@ class IapPlugin {
 
                     productDict["subscriptionOfferDetails"] = subscriptionOffers
                 }
-            } else {
-                // One-time purchase
-                productDict["priceAmountMicros"] = 0  // Not available in StoreKit 2
             }
 
             productsArray.append(productDict)
@@ -109,11 +119,21 @@ class IapPlugin {
         return try serializeToJSON(["products": productsArray])
     }
 
-    public func purchase(productId: RustString, productType: RustString, offerToken: RustString?)
-        async throws(FFIResult) -> String
-    {
+    public func purchase(
+        productId: RustString, productType: RustString, offerToken: RustString?,
+        appAccountToken: RustString?
+    ) async throws(FFIResult) -> String {
         let id = productId.as_str().toString()
 
+        var purchaseOptions: Set<Product.PurchaseOption> = []
+        if let appAccountToken = appAccountToken?.as_str().toString() {
+            guard let uuid = UUID(uuidString: appAccountToken) else {
+                throw FFIResult.Err(
+                    RustString("Invalid appAccountToken: must be a valid UUID string"))
+            }
+            purchaseOptions.insert(.appAccountToken(uuid))
+        }
+
         let products: [Product]
         do {
             products = try await Product.products(for: [id])
@@ -129,7 +149,7 @@ class IapPlugin {
         // Initiate purchase
         let result: Product.PurchaseResult
         do {
-            result = try await product.purchase()
+            result = try await product.purchase(options: purchaseOptions)
         } catch {
             throw FFIResult.Err(RustString("Purchase failed: \(error.localizedDescription)"))
         }
@@ -152,7 +172,21 @@ class IapPlugin {
             throw FFIResult.Err(RustString("Purchase cancelled by user"))
 
         case .pending:
-            throw FFIResult.Err(RustString("Purchase is pending"))
+            // Completed or declined later through Transaction.updates
+            var purchase: JsonObject = [
+                "packageName": Bundle.main.bundleIdentifier ?? "",
+                "productId": id,
+                "purchaseToken": "",
+                "purchaseState": PurchaseStateValue.pending.rawValue,
+                "isAutoRenewing": false,
+                "isAcknowledged": false,
+                "originalJson": "",
+                "signature": "",
+            ]
+            if let appAccountToken = appAccountToken?.as_str().toString() {
+                purchase["accountToken"] = appAccountToken.lowercased()
+            }
+            return try serializeToJSON(purchase)
 
         @unknown default:
             throw FFIResult.Err(RustString("Unknown purchase result"))
@@ -232,6 +266,7 @@ class IapPlugin {
                     statusResult["purchaseTime"] = Int(
                         transaction.purchaseDate.timeIntervalSince1970 * 1000)
                     statusResult["purchaseToken"] = String(transaction.id)
+                    statusResult["ownershipType"] = ownershipType(of: transaction)
                     statusResult["isAcknowledged"] = true  // Always true on macOS
 
                     // Check if expired/revoked
@@ -290,8 +325,104 @@ class IapPlugin {
         return try serializeToJSON(statusResult)
     }
 
+    public func getStorefront() async throws(FFIResult) -> String {
+        guard let storefront = await Storefront.current else {
+            throw FFIResult.Err(RustString("Storefront not available"))
+        }
+        return try serializeToJSON(createStorefrontObject(from: storefront))
+    }
+
+    public func canMakePayments() -> Bool {
+        return AppStore.canMakePayments
+    }
+
+    public func openUrl(url: RustString) -> Bool {
+        guard let url = URL(string: url.as_str().toString()) else {
+            return false
+        }
+        return NSWorkspace.shared.open(url)
+    }
+
+    public func getAppLicense() async throws(FFIResult) -> String {
+        let result: VerificationResult<AppTransaction>
+        do {
+            result = try await AppTransaction.shared
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to get app transaction: \(error.localizedDescription)"))
+        }
+        return try serializeToJSON(createAppLicenseObject(from: result))
+    }
+
+    public func presentOfferCodeRedeemSheet() async throws(FFIResult) -> String {
+        guard #available(macOS 15.0, *) else {
+            throw FFIResult.Err(RustString("Offer code redemption requires macOS 15.0 or later"))
+        }
+        guard
+            let viewController = await MainActor.run(body: {
+                NSApp.keyWindow?.contentViewController
+            })
+        else {
+            throw FFIResult.Err(
+                RustString("No view controller to present the redemption sheet from"))
+        }
+
+        do {
+            try await AppStore.presentOfferCodeRedeemSheet(from: viewController)
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to present redemption sheet: \(error.localizedDescription)"))
+        }
+        return try serializeToJSON([:])
+    }
+
+    public func beginRefundRequest(transactionId: RustString) async throws(FFIResult) -> String {
+        guard let id = UInt64(transactionId.as_str().toString()) else {
+            throw FFIResult.Err(RustString("Invalid transaction ID"))
+        }
+        guard let window = await MainActor.run(body: { NSApp.keyWindow ?? NSApp.windows.first })
+        else {
+            throw FFIResult.Err(RustString("No window to present the refund sheet from"))
+        }
+
+        let status: Transaction.RefundRequestStatus
+        do {
+            status = try await Transaction.beginRefundRequest(for: id, in: window)
+        } catch {
+            throw FFIResult.Err(
+                RustString("Refund request failed: \(error.localizedDescription)"))
+        }
+        return try serializeToJSON(["status": status == .success ? "success" : "userCancelled"])
+    }
+
+    public func getAppTransaction(refresh: Bool) async throws(FFIResult) -> String {
+        let result: VerificationResult<AppTransaction>
+        do {
+            // Refreshing may prompt the user to sign in to the App Store
+            result = refresh ? try await AppTransaction.refresh() : try await AppTransaction.shared
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to get app transaction: \(error.localizedDescription)"))
+        }
+
+        guard case .verified(let appTransaction) = result else {
+            throw FFIResult.Err(RustString("App transaction not verified"))
+        }
+        return try serializeToJSON(
+            createAppTransactionObject(
+                from: appTransaction, jwsRepresentation: result.jwsRepresentation))
+    }
+
     // MARK: - Helper Functions
 
+    private func createStorefrontObject(from storefront: Storefront) -> JsonObject {
+        // StoreKit doesn't expose the storefront currency
+        return [
+            "countryCode": storefront.countryCode,
+            "id": storefront.id,
+        ]
+    }
+
     private func handleTransactionUpdate(_ result: VerificationResult<Transaction>) async {
         switch result {
         case .verified(let transaction):
@@ -300,6 +431,9 @@ class IapPlugin {
                 if let purchase = try? await createPurchaseObject(from: result, product: product),
                    let jsonString = try? serializeToJSON(purchase) {
                     try? trigger("purchaseUpdated", jsonString)
+                    if transaction.revocationDate != nil {
+                        try? trigger("purchaseRevoked", jsonString)
+                    }
                 }
             }
 
@@ -312,6 +446,50 @@ class IapPlugin {
         }
     }
 
+    private func createAppLicenseObject(from result: VerificationResult<AppTransaction>)
+        -> JsonObject
+    {
+        let appTransaction = result.unsafePayloadValue
+        let isVerified: Bool
+        switch result {
+        case .verified:
+            isVerified = true
+        case .unverified:
+            isVerified = false
+        }
+
+        return [
+            "isApplicable": true,
+            "isActive": isVerified,
+            "isTrial": false,  // The App Store has no app trials
+            "originalAppVersion": appTransaction.originalAppVersion,
+            "originalPurchaseTime": Int(
+                appTransaction.originalPurchaseDate.timeIntervalSince1970 * 1000),
+            "jwsRepresentation": result.jwsRepresentation,
+        ]
+    }
+
+    private func createAppTransactionObject(
+        from appTransaction: AppTransaction, jwsRepresentation: String
+    ) -> JsonObject {
+        var object: JsonObject = [
+            "bundleId": appTransaction.bundleID,
+            "appVersion": appTransaction.appVersion,
+            "originalAppVersion": appTransaction.originalAppVersion,
+            "originalPurchaseTime": Int(
+                appTransaction.originalPurchaseDate.timeIntervalSince1970 * 1000),
+            "environment": appTransaction.environment.rawValue.lowercased(),
+            "signedTime": Int(appTransaction.signedDate.timeIntervalSince1970 * 1000),
+            "deviceVerification": appTransaction.deviceVerification.base64EncodedString(),
+            "deviceVerificationNonce": appTransaction.deviceVerificationNonce.uuidString,
+            "jwsRepresentation": jwsRepresentation,
+        ]
+        if #available(iOS 17.2, macOS 14.2, *), let preorderDate = appTransaction.preorderDate {
+            object["preorderTime"] = Int(preorderDate.timeIntervalSince1970 * 1000)
+        }
+        return object
+    }
+
     private func serializeToJSON(_ object: JsonObject) throws(FFIResult) -> String {
         guard let data = try? JSONSerialization.data(withJSONObject: object),
             let jsonString = String(data: data, encoding: .utf8)
@@ -336,6 +514,14 @@ class IapPlugin {
         }
     }
 
+    /// Price in micros (amount × 1,000,000), rounded to the nearest micro.
+    private func priceMicros(_ price: Decimal) -> Int64 {
+        var micros = price * 1_000_000
+        var rounded = Decimal()
+        NSDecimalRound(&rounded, &micros, 0, .plain)
+        return NSDecimalNumber(decimal: rounded).int64Value
+    }
+
     private func getCurrencyCode(for product: Product) -> String {
         if #available(macOS 13.0, *) {
             return product.priceFormatStyle.locale.currency?.identifier ?? ""
@@ -367,7 +553,7 @@ class IapPlugin {
             }
         }
 
-        return [
+        var purchase: JsonObject = [
             "orderId": String(transaction.id),
             "originalId": String(transaction.originalID),
             "jwsRepresentation": verificationResult.jwsRepresentation,
@@ -379,9 +565,34 @@ class IapPlugin {
                 ? PurchaseStateValue.purchased.rawValue : PurchaseStateValue.canceled.rawValue,
             "isAutoRenewing": isAutoRenewing,
             "isAcknowledged": true,  // Always true on macOS
-            "originalJson": "",  // Not available in StoreKit 2
+            "originalJson": String(decoding: transaction.jsonRepresentation, as: UTF8.self),
             "signature": "",  // Not available in StoreKit 2
         ]
+        if let revocationDate = transaction.revocationDate {
+            purchase["revocationTime"] = Int(revocationDate.timeIntervalSince1970 * 1000)
+            purchase["revocationReason"] =
+                transaction.revocationReason == .developerIssue ? "developerIssue" : "other"
+        }
+        if let appAccountToken = transaction.appAccountToken {
+            purchase["accountToken"] = appAccountToken.uuidString.lowercased()
+        }
+        purchase["ownershipType"] = ownershipType(of: transaction)
+        purchase["environment"] = environment(of: transaction)
+        purchase["transactionId"] = String(transaction.id)
+        purchase["quantity"] = transaction.purchasedQuantity
+        if let webOrderLineItemID = transaction.webOrderLineItemID {
+            purchase["webOrderLineItemId"] = webOrderLineItemID
+        }
+        return purchase
+    }
+
+    /// "production", "sandbox" or "xcode"
+    private func environment(of transaction: Transaction) -> String {
+        transaction.environment.rawValue.lowercased()
+    }
+
+    private func ownershipType(of transaction: Transaction) -> String {
+        transaction.ownershipType == .familyShared ? "familyShared" : "purchased"
     }
 }
 
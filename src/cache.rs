@@ -0,0 +1,369 @@
+//! Product catalog cache shared by the platform backends.
+//!
+//! Products are cached per product id and requested product type for the
+//! configured TTL. Fetches for the same product type are serialized, and each
+//! caller re-checks the cache once it holds the fetch lock, so concurrent
+//! requests for the same ids result in a single store query.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::models::*;
+
+type CacheKey = (String, String);
+
+struct CacheEntry {
+    product: Product,
+    fetched_at: Instant,
+}
+
+pub(crate) struct ProductCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+    fetch_locks: Mutex<HashMap<String, Arc<tauri::async_runtime::Mutex<()>>>>,
+}
+
+impl ProductCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            fetch_locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a cached product if it is still fresh.
+    pub(crate) fn get(&self, product_id: &str, product_type: &str) -> Option<Product> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(&(product_id.to_string(), product_type.to_string()))
+            .filter(|entry| entry.fetched_at.elapsed() < self.ttl)
+            .map(|entry| entry.product.clone())
+    }
+
+    /// Returns the requested products, calling `fetch` once with the ids that
+    /// are missing or stale. With `refresh`, every requested id is fetched.
+    ///
+    /// Products are returned in the order of `product_ids`; ids the store did
+    /// not return are reported in `invalid_product_ids` and not cached.
+    pub(crate) async fn get_products<F, Fut>(
+        &self,
+        product_ids: Vec<String>,
+        product_type: String,
+        refresh: bool,
+        fetch: F,
+    ) -> crate::Result<GetProductsResponse>
+    where
+        F: FnOnce(Vec<String>, String) -> Fut,
+        Fut: Future<Output = crate::Result<GetProductsResponse>>,
+    {
+        if self.ttl.is_zero() {
+            let response = fetch(product_ids.clone(), product_type).await?;
+            return Ok(GetProductsResponse::from_requested(
+                &product_ids,
+                response.products,
+            ));
+        }
+
+        if !refresh {
+            if let Some(products) = self.lookup_all(&product_ids, &product_type) {
+                log::debug!("Product cache hit for {product_type} {product_ids:?}");
+                return Ok(GetProductsResponse::from_requested(&product_ids, products));
+            }
+        }
+
+        let fetch_lock = self.fetch_lock(&product_type);
+        let _guard = fetch_lock.lock().await;
+
+        // Another caller may have fetched the same ids while we waited.
+        let missing: Vec<String> = if refresh {
+            product_ids.clone()
+        } else {
+            product_ids
+                .iter()
+                .filter(|id| self.get(id, &product_type).is_none())
+                .cloned()
+                .collect()
+        };
+
+        if missing.is_empty() {
+            log::debug!(
+                "Product cache hit for {product_type} {product_ids:?} after waiting for fetch"
+            );
+        } else {
+            log::debug!(
+                "Product cache {} for {product_type} {missing:?}",
+                if refresh { "refresh" } else { "miss" }
+            );
+            let response = fetch(missing, product_type.clone()).await?;
+            self.insert(&product_type, response.products);
+        }
+
+        let products = product_ids
+            .iter()
+            .filter_map(|id| self.get(id, &product_type))
+            .collect();
+        Ok(GetProductsResponse::from_requested(&product_ids, products))
+    }
+
+    fn lookup_all(&self, product_ids: &[String], product_type: &str) -> Option<Vec<Product>> {
+        product_ids
+            .iter()
+            .map(|id| self.get(id, product_type))
+            .collect()
+    }
+
+    /// Caches freshly fetched products, dropping expired entries so the map
+    /// does not grow with every id ever requested.
+    fn insert(&self, product_type: &str, products: Vec<Product>) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|_, entry| entry.fetched_at.elapsed() < self.ttl);
+        let fetched_at = Instant::now();
+        for product in products {
+            entries.insert(
+                (product.product_id.clone(), product_type.to_string()),
+                CacheEntry {
+                    product,
+                    fetched_at,
+                },
+            );
+        }
+    }
+
+    fn fetch_lock(&self, product_type: &str) -> Arc<tauri::async_runtime::Mutex<()>> {
+        match self.fetch_locks.lock() {
+            Ok(mut locks) => locks.entry(product_type.to_string()).or_default().clone(),
+            // A poisoned map only loses coalescing, not correctness.
+            Err(_) => Arc::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn product(product_id: &str) -> Product {
+        Product {
+            product_id: product_id.to_string(),
+            title: product_id.to_string(),
+            description: String::new(),
+            product_type: "inapp".to_string(),
+            formatted_price: None,
+            price: None,
+            subscription_offer_details: None,
+        }
+    }
+
+    fn respond(ids: Vec<String>) -> crate::Result<GetProductsResponse> {
+        Ok(GetProductsResponse::from_requested(
+            &ids,
+            ids.iter()
+                .filter(|id| !id.starts_with("invalid"))
+                .map(|id| product(id))
+                .collect(),
+        ))
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cache_hit_skips_fetch() {
+        let cache = ProductCache::new(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        tauri::async_runtime::block_on(async {
+            for _ in 0..2 {
+                let response = cache
+                    .get_products(ids(&["a", "b"]), "inapp".to_string(), false, |ids, _| {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        async move { respond(ids) }
+                    })
+                    .await
+                    .expect("Failed to get products");
+                assert_eq!(response.products.len(), 2);
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_cache_fetches_only_missing_ids() {
+        let cache = ProductCache::new(Duration::from_secs(60));
+
+        tauri::async_runtime::block_on(async {
+            cache
+                .get_products(
+                    ids(&["a"]),
+                    "inapp".to_string(),
+                    false,
+                    |ids, _| async move { respond(ids) },
+                )
+                .await
+                .expect("Failed to get products");
+
+            let response = cache
+                .get_products(ids(&["b", "a"]), "inapp".to_string(), false, |ids, _| {
+                    assert_eq!(ids, vec!["b".to_string()]);
+                    async move { respond(ids) }
+                })
+                .await
+                .expect("Failed to get products");
+
+            let returned: Vec<_> = response.products.iter().map(|p| &p.product_id).collect();
+            assert_eq!(returned, vec!["b", "a"]);
+        });
+    }
+
+    #[test]
+    fn test_cache_refresh_bypasses_cache() {
+        let cache = ProductCache::new(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        tauri::async_runtime::block_on(async {
+            for refresh in [false, true] {
+                cache
+                    .get_products(ids(&["a"]), "inapp".to_string(), refresh, |ids, _| {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        async move { respond(ids) }
+                    })
+                    .await
+                    .expect("Failed to get products");
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_cache_keyed_by_product_type() {
+        let cache = ProductCache::new(Duration::from_secs(60));
+
+        tauri::async_runtime::block_on(async {
+            cache
+                .get_products(
+                    ids(&["a"]),
+                    "inapp".to_string(),
+                    false,
+                    |ids, _| async move { respond(ids) },
+                )
+                .await
+                .expect("Failed to get products");
+        });
+
+        assert!(cache.get("a", "inapp").is_some());
+        assert!(cache.get("a", "subs").is_none());
+    }
+
+    #[test]
+    fn test_cache_reports_invalid_product_ids() {
+        let cache = ProductCache::new(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        tauri::async_runtime::block_on(async {
+            for _ in 0..2 {
+                let response = cache
+                    .get_products(
+                        ids(&["a", "invalid_b"]),
+                        "inapp".to_string(),
+                        false,
+                        |ids, _| {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            async move { respond(ids) }
+                        },
+                    )
+                    .await
+                    .expect("Failed to get products");
+                assert_eq!(response.products.len(), 1);
+                assert_eq!(response.invalid_product_ids, vec!["invalid_b"]);
+            }
+        });
+
+        // Invalid ids are not cached, so they are queried again.
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_cache_evicts_expired_entries() {
+        let cache = ProductCache::new(Duration::from_millis(20));
+
+        cache.insert("inapp", vec![product("a")]);
+        std::thread::sleep(Duration::from_millis(40));
+        cache.insert("subs", vec![product("b")]);
+
+        let entries = cache.entries.lock().expect("Cache lock poisoned");
+        assert_eq!(entries.len(), 1);
+        assert!(entries.contains_key(&("b".to_string(), "subs".to_string())));
+    }
+
+    #[test]
+    fn test_cache_zero_ttl_always_fetches() {
+        let cache = ProductCache::new(Duration::ZERO);
+        let calls = AtomicUsize::new(0);
+
+        tauri::async_runtime::block_on(async {
+            for _ in 0..2 {
+                cache
+                    .get_products(ids(&["a"]), "inapp".to_string(), false, |ids, _| {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        async move { respond(ids) }
+                    })
+                    .await
+                    .expect("Failed to get products");
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(cache.get("a", "inapp").is_none());
+    }
+
+    #[test]
+    fn test_cache_coalesces_concurrent_requests() {
+        let cache = Arc::new(ProductCache::new(Duration::from_secs(60)));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = tauri::async_runtime::channel::<()>(2);
+        let rx = Arc::new(tauri::async_runtime::Mutex::new(rx));
+
+        let spawn_request = || {
+            let cache = cache.clone();
+            let calls = calls.clone();
+            let rx = rx.clone();
+            tauri::async_runtime::spawn(async move {
+                cache
+                    .get_products(ids(&["a"]), "inapp".to_string(), false, |ids, _| {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        async move {
+                            rx.lock().await.recv().await;
+                            respond(ids)
+                        }
+                    })
+                    .await
+            })
+        };
+
+        tauri::async_runtime::block_on(async {
+            let first = spawn_request();
+            let second = spawn_request();
+            tx.send(()).await.expect("Failed to release fetch");
+            tx.send(()).await.expect("Failed to release fetch");
+
+            for handle in [first, second] {
+                let response = handle
+                    .await
+                    .expect("Request task panicked")
+                    .expect("Failed to get products");
+                assert_eq!(response.products.len(), 1);
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,4 +1,4 @@
-use tauri::{command, AppHandle, Runtime};
+use tauri::{command, AppHandle, Runtime, Webview};
 
 use crate::models::*;
 use crate::{IapExt, Result};
@@ -13,16 +13,30 @@ pub(crate) async fn get_products<R: Runtime>(
     app: AppHandle<R>,
     payload: GetProductsRequest,
 ) -> Result<GetProductsResponse> {
-    app.iap()
-        .get_products(payload.product_ids, payload.product_type)
-        .await
+    let response = app
+        .iap()
+        .get_products(payload.product_ids, payload.product_type, payload.refresh)
+        .await?;
+
+    if payload.strict {
+        response.into_strict()
+    } else {
+        Ok(response)
+    }
 }
 
 #[command]
 pub(crate) async fn purchase<R: Runtime>(
     app: AppHandle<R>,
+    webview: Webview<R>,
     payload: PurchaseRequest,
 ) -> Result<Purchase> {
+    // The Microsoft Store purchase dialog is modal to the invoking window
+    #[cfg(target_os = "windows")]
+    app.iap().set_parent_window(&webview.window());
+    #[cfg(not(target_os = "windows"))]
+    let _ = webview;
+
     app.iap().purchase(payload).await
 }
 
@@ -31,7 +45,13 @@ pub(crate) async fn restore_purchases<R: Runtime>(
     app: AppHandle<R>,
     payload: RestorePurchasesRequest,
 ) -> Result<RestorePurchasesResponse> {
-    app.iap().restore_purchases(payload.product_type).await
+    let mut response = app.iap().restore_purchases(payload.product_type).await?;
+    if let Some(ownership_type) = payload.ownership_type {
+        response
+            .purchases
+            .retain(|purchase| purchase.ownership_type == ownership_type);
+    }
+    Ok(response)
 }
 
 #[command]
@@ -57,5 +77,75 @@ pub(crate) async fn consume_purchase<R: Runtime>(
     app: AppHandle<R>,
     payload: ConsumePurchaseRequest,
 ) -> Result<ConsumePurchaseResponse> {
-    app.iap().consume_purchase(payload.purchase_token).await
+    app.iap()
+        .consume_purchase(payload.purchase_token, payload.quantity)
+        .await
+}
+
+#[command]
+pub(crate) async fn get_consumable_balance_remaining<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetConsumableBalanceRequest,
+) -> Result<ConsumableBalance> {
+    app.iap()
+        .get_consumable_balance_remaining(payload.product_id)
+        .await
+}
+
+#[command]
+pub(crate) async fn get_storefront<R: Runtime>(app: AppHandle<R>) -> Result<Storefront> {
+    app.iap().get_storefront().await
+}
+
+#[command]
+pub(crate) async fn get_app_license<R: Runtime>(app: AppHandle<R>) -> Result<AppLicense> {
+    app.iap().get_app_license().await
+}
+
+#[command]
+pub(crate) async fn get_app_transaction<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetAppTransactionRequest,
+) -> Result<AppTransaction> {
+    app.iap().get_app_transaction(payload.refresh).await
+}
+
+#[command]
+pub(crate) async fn begin_refund_request<R: Runtime>(
+    app: AppHandle<R>,
+    payload: RefundRequest,
+) -> Result<RefundRequestResponse> {
+    app.iap().begin_refund_request(payload.transaction_id).await
+}
+
+#[command]
+pub(crate) async fn show_manage_subscriptions<R: Runtime>(
+    app: AppHandle<R>,
+    payload: ShowManageSubscriptionsRequest,
+) -> Result<()> {
+    app.iap()
+        .show_manage_subscriptions(payload.product_id)
+        .await
+}
+
+#[command]
+pub(crate) async fn present_code_redemption<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.iap().present_code_redemption().await
+}
+
+#[command]
+pub(crate) async fn get_pending_purchases<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<GetPendingPurchasesResponse> {
+    app.iap().get_pending_purchases().await
+}
+
+#[command]
+pub(crate) async fn get_capabilities<R: Runtime>(app: AppHandle<R>) -> Result<Capabilities> {
+    app.iap().capabilities().await
+}
+
+#[command]
+pub(crate) fn compare_prices(payload: ComparePricesRequest) -> Result<ComparePricesResponse> {
+    crate::pricing::compare_prices(&payload.products, payload.reference_product_id.as_deref())
 }
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Plugin configuration, read from `plugins.iap` in `tauri.conf.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    /// How long products fetched from the store are served from the cache,
+    /// in seconds. `0` disables caching.
+    #[serde(default = "default_product_cache_ttl_secs")]
+    pub product_cache_ttl_secs: u64,
+    /// Label of the window the Microsoft Store UI is attached to on Windows.
+    /// Defaults to the window that invoked the command.
+    #[serde(default)]
+    pub window_label: Option<String>,
+    /// How long a Microsoft Store request may take on Windows before it is
+    /// cancelled, in seconds. `0` disables the timeout. Purchases are not
+    /// limited since they wait for the user.
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+fn default_product_cache_ttl_secs() -> u64 {
+    300
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            product_cache_ttl_secs: default_product_cache_ttl_secs(),
+            window_label: None,
+            request_timeout_secs: default_request_timeout_secs(),
+        }
+    }
+}
+
+impl Config {
+    pub fn product_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.product_cache_ttl_secs)
+    }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout_secs > 0).then(|| Duration::from_secs(self.request_timeout_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() {
+        let config: Config = serde_json::from_str("{}").expect("Failed to deserialize Config");
+        assert_eq!(config.product_cache_ttl(), Duration::from_secs(300));
+        assert_eq!(config.window_label, None);
+        assert_eq!(config.request_timeout(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_config_request_timeout_disabled() {
+        let config: Config = serde_json::from_str(r#"{"requestTimeoutSecs":0}"#)
+            .expect("Failed to deserialize Config");
+        assert_eq!(config.request_timeout(), None);
+    }
+
+    #[test]
+    fn test_config_product_cache_ttl() {
+        let config: Config = serde_json::from_str(r#"{"productCacheTtlSecs":0}"#)
+            .expect("Failed to deserialize Config");
+        assert_eq!(config.product_cache_ttl(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_config_window_label() {
+        let config: Config = serde_json::from_str(r#"{"windowLabel":"store"}"#)
+            .expect("Failed to deserialize Config");
+        assert_eq!(config.window_label.as_deref(), Some("store"));
+    }
+}
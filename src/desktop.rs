@@ -1,11 +1,11 @@
-use serde::de::DeserializeOwned;
 use tauri::{plugin::PluginApi, AppHandle, Runtime};
 
 use crate::models::*;
+use crate::Config;
 
-pub fn init<R: Runtime, C: DeserializeOwned>(
+pub fn init<R: Runtime>(
     app: &AppHandle<R>,
-    _api: PluginApi<R, C>,
+    _api: PluginApi<R, Option<Config>>,
 ) -> crate::Result<Iap<R>> {
     Ok(Iap(app.clone()))
 }
@@ -18,6 +18,7 @@ impl<R: Runtime> Iap<R> {
         &self,
         _product_ids: Vec<String>,
         _product_type: String,
+        _refresh: bool,
     ) -> crate::Result<GetProductsResponse> {
         Err(crate::Error::from(std::io::Error::other(
             "IAP is not supported on this platform",
@@ -67,9 +68,77 @@ impl<R: Runtime> Iap<R> {
     pub async fn consume_purchase(
         &self,
         _purchase_token: String,
+        _quantity: u32,
     ) -> crate::Result<ConsumePurchaseResponse> {
         Err(crate::Error::from(std::io::Error::other(
             "IAP is not supported on this platform",
         )))
     }
+
+    pub async fn get_consumable_balance_remaining(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<ConsumableBalance> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn get_storefront(&self) -> crate::Result<Storefront> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn get_app_license(&self) -> crate::Result<AppLicense> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn get_app_transaction(&self, _refresh: bool) -> crate::Result<AppTransaction> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn begin_refund_request(
+        &self,
+        _transaction_id: String,
+    ) -> crate::Result<RefundRequestResponse> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn show_manage_subscriptions(
+        &self,
+        _product_id: Option<String>,
+    ) -> crate::Result<()> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn present_code_redemption(&self) -> crate::Result<()> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn get_pending_purchases(&self) -> crate::Result<GetPendingPurchasesResponse> {
+        Err(crate::Error::from(std::io::Error::other(
+            "IAP is not supported on this platform",
+        )))
+    }
+
+    pub async fn capabilities(&self) -> crate::Result<Capabilities> {
+        Ok(Capabilities {
+            backend: StoreBackend::Unsupported,
+            supported_operations: Vec::new(),
+            prerequisites_met: false,
+            prerequisite_error: Some("IAP is not supported on this platform".to_string()),
+            can_make_payments: false,
+        })
+    }
 }
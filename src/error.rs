@@ -59,6 +59,10 @@ pub enum Error {
     #[cfg(target_os = "windows")]
     #[error(transparent)]
     WindowsApi(#[from] windows::core::Error),
+    #[error("Product not found: {0}")]
+    ProductNotFound(String),
+    #[error("Products not found: {}", .0.join(", "))]
+    ProductsNotFound(Vec<String>),
 }
 
 impl Serialize for Error {
@@ -97,6 +101,18 @@ mod tests {
         assert!(error.to_string().contains("access denied"));
     }
 
+    #[test]
+    fn test_error_product_not_found_display() {
+        let error = Error::ProductNotFound("com.example.premium".to_string());
+        assert_eq!(error.to_string(), "Product not found: com.example.premium");
+    }
+
+    #[test]
+    fn test_error_products_not_found_display() {
+        let error = Error::ProductsNotFound(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(error.to_string(), "Products not found: a, b");
+    }
+
     #[cfg(desktop)]
     mod desktop_tests {
         use super::*;
@@ -0,0 +1,28 @@
+#!/bin/sh
+# Regenerates the local certificate chain the App Store tests sign with. It
+# mirrors Apple's: a P-384 root, an intermediate and a P-256 signing key,
+# carrying Apple's intermediate and leaf marker extensions.
+set -e
+cd "$(dirname "$0")"
+dir=$(mktemp -d)
+dates="-not_before 20240101000000Z -not_after 20340101000000Z"
+
+for name in root other_root; do
+    openssl ecparam -name secp384r1 -genkey -noout -out "$dir/$name.key"
+    openssl req -new -x509 -key "$dir/$name.key" -sha384 -subj "/CN=$name" $dates \
+        -config certificates.cnf -extensions root -outform DER -out "$name.der"
+done
+
+openssl ecparam -name prime256v1 -genkey -noout -out "$dir/intermediate.key"
+openssl req -new -key "$dir/intermediate.key" -subj "/CN=intermediate" -config certificates.cnf \
+    -out "$dir/intermediate.csr"
+openssl x509 -req -in "$dir/intermediate.csr" -CA root.der -CAkey "$dir/root.key" -sha384 $dates \
+    -set_serial 2 -extfile certificates.cnf -extensions intermediate -outform DER -out intermediate.der
+
+openssl ecparam -name prime256v1 -genkey -noout -out "$dir/leaf.key"
+openssl req -new -key "$dir/leaf.key" -subj "/CN=leaf" -config certificates.cnf -out "$dir/leaf.csr"
+openssl x509 -req -in "$dir/leaf.csr" -CA intermediate.der -CAkey "$dir/intermediate.key" -sha256 $dates \
+    -set_serial 3 -extfile certificates.cnf -extensions leaf -outform DER -out leaf.der
+openssl pkcs8 -topk8 -nocrypt -in "$dir/leaf.key" -outform DER -out leaf.pk8.der
+
+rm -rf "$dir"
@@ -3,6 +3,7 @@ use tauri::{
     Manager, Runtime,
 };
 
+pub use config::Config;
 pub use models::*;
 
 #[cfg(target_os = "linux")]
@@ -14,11 +15,24 @@ mod mobile;
 #[cfg(target_os = "windows")]
 mod windows;
 
+mod cache;
 mod commands;
+mod config;
 mod error;
+pub mod links;
 #[cfg(desktop)]
 mod listeners;
 mod models;
+// Only macOS reports store updates through Rust
+#[cfg(any(target_os = "macos", test))]
+mod pending;
+pub mod pricing;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "validation")]
+pub mod server_validation;
+#[cfg(any(target_os = "macos", target_os = "windows", feature = "validation"))]
+mod time;
 
 pub use error::{Error, Result};
 
@@ -43,8 +57,8 @@ impl<R: Runtime, T: Manager<R>> crate::IapExt<R> for T {
 }
 
 /// Initializes the plugin.
-pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("iap")
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<Config>> {
+    Builder::<R, Option<Config>>::new("iap")
         .invoke_handler(tauri::generate_handler![
             commands::initialize,
             commands::get_products,
@@ -52,7 +66,17 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             commands::restore_purchases,
             commands::acknowledge_purchase,
             commands::consume_purchase,
+            commands::get_consumable_balance_remaining,
             commands::get_product_status,
+            commands::get_storefront,
+            commands::get_capabilities,
+            commands::get_app_license,
+            commands::get_app_transaction,
+            commands::begin_refund_request,
+            commands::show_manage_subscriptions,
+            commands::present_code_redemption,
+            commands::get_pending_purchases,
+            commands::compare_prices,
             #[cfg(desktop)]
             listeners::register_listener,
             #[cfg(desktop)]
@@ -0,0 +1,98 @@
+//! Deep links into the stores' own subscription management pages.
+//!
+//! Used where the platform has no in-app sheet for managing subscriptions.
+//! The builders are pure so the URLs can be checked without a store.
+
+/// Subscriptions page of the Mac App Store, which has no in-app
+/// manage-subscriptions sheet.
+pub const APP_STORE_SUBSCRIPTIONS_URL: &str = "macappstores://apps.apple.com/account/subscriptions";
+
+/// Microsoft account page listing the user's subscriptions.
+pub const MICROSOFT_SUBSCRIPTIONS_URL: &str = "https://account.microsoft.com/services";
+
+/// Google Play page for redeeming promo codes.
+pub const PLAY_REDEEM_URL: &str = "https://play.google.com/redeem";
+
+/// Google Play subscription center, opened on `product_id` if given.
+pub fn play_subscriptions_url(package_name: &str, product_id: Option<&str>) -> String {
+    const BASE: &str = "https://play.google.com/store/account/subscriptions";
+
+    match product_id {
+        Some(product_id) => format!(
+            "{BASE}?sku={}&package={}",
+            encode_query_value(product_id),
+            encode_query_value(package_name)
+        ),
+        None => BASE.to_string(),
+    }
+}
+
+/// Microsoft Store page of the add-on `store_id`, where its subscription is
+/// managed, or the account subscriptions page if no add-on is given.
+pub fn microsoft_store_subscriptions_uri(store_id: Option<&str>) -> String {
+    match store_id {
+        Some(store_id) => format!(
+            "ms-windows-store://pdp/?ProductId={}",
+            encode_query_value(store_id)
+        ),
+        None => MICROSOFT_SUBSCRIPTIONS_URL.to_string(),
+    }
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_subscriptions_url() {
+        assert_eq!(
+            play_subscriptions_url("com.example.app", Some("premium_monthly")),
+            "https://play.google.com/store/account/subscriptions?sku=premium_monthly&package=com.example.app"
+        );
+        assert_eq!(
+            play_subscriptions_url("com.example.app", None),
+            "https://play.google.com/store/account/subscriptions"
+        );
+    }
+
+    #[test]
+    fn test_play_subscriptions_url_encodes_values() {
+        assert_eq!(
+            play_subscriptions_url("com.example.app", Some("pro plan&x=1")),
+            "https://play.google.com/store/account/subscriptions?sku=pro%20plan%26x%3D1&package=com.example.app"
+        );
+    }
+
+    #[test]
+    fn test_microsoft_store_subscriptions_uri() {
+        assert_eq!(
+            microsoft_store_subscriptions_uri(Some("9NBLGGH4TNNR")),
+            "ms-windows-store://pdp/?ProductId=9NBLGGH4TNNR"
+        );
+        assert_eq!(
+            microsoft_store_subscriptions_uri(None),
+            "https://account.microsoft.com/services"
+        );
+    }
+
+    #[test]
+    fn test_encode_query_value() {
+        assert_eq!(encode_query_value("a-b_c.d~e"), "a-b_c.d~e");
+        assert_eq!(encode_query_value("a/b?c"), "a%2Fb%3Fc");
+        assert_eq!(encode_query_value("é"), "%C3%A9");
+    }
+}
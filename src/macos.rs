@@ -1,7 +1,11 @@
 use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
 use tauri::{plugin::PluginApi, AppHandle, Runtime};
 
+use crate::cache::ProductCache;
 use crate::models::*;
+use crate::pending::{PendingPurchases, PendingResolution};
+use crate::Config;
 
 /// Validation checks for macOS IAP functionality.
 ///
@@ -59,6 +63,7 @@ mod ffi {
             productId: String,
             productType: String,
             offerToken: Option<String>,
+            appAccountToken: Option<String>,
         ) -> Result<String, FFIResult>;
         async fn restorePurchases(&self, productType: String) -> Result<String, FFIResult>;
         async fn acknowledgePurchase(&self, purchaseToken: String) -> Result<String, FFIResult>;
@@ -68,9 +73,32 @@ mod ffi {
             productId: String,
             productType: String,
         ) -> Result<String, FFIResult>;
+        async fn getStorefront(&self) -> Result<String, FFIResult>;
+        async fn getAppLicense(&self) -> Result<String, FFIResult>;
+        async fn getAppTransaction(&self, refresh: bool) -> Result<String, FFIResult>;
+        async fn beginRefundRequest(&self, transactionId: String) -> Result<String, FFIResult>;
+        fn canMakePayments(&self) -> bool;
+        fn openUrl(&self, url: String) -> bool;
+        async fn presentOfferCodeRedeemSheet(&self) -> Result<String, FFIResult>;
     }
 }
 
+const SUPPORTED_OPERATIONS: &[StoreOperation] = &[
+    StoreOperation::GetProducts,
+    StoreOperation::Purchase,
+    StoreOperation::RestorePurchases,
+    StoreOperation::AcknowledgePurchase,
+    StoreOperation::ConsumePurchase,
+    StoreOperation::GetProductStatus,
+    StoreOperation::GetStorefront,
+    StoreOperation::GetAppLicense,
+    StoreOperation::GetAppTransaction,
+    StoreOperation::BeginRefundRequest,
+    StoreOperation::ShowManageSubscriptions,
+    StoreOperation::PresentCodeRedemption,
+    StoreOperation::GetPendingPurchases,
+];
+
 /// Extension trait for parsing FFI responses from Swift into typed Rust results.
 trait ParseFfiResponse {
     /// Deserializes a JSON response into the target type, converting FFI errors
@@ -97,17 +125,65 @@ impl ParseFfiResponse for Result<String, ffi::FFIResult> {
 
 /// Called by Swift via FFI when transaction updates occur.
 fn trigger(event: String, payload: String) -> Result<(), ffi::FFIResult> {
+    if event == "purchaseUpdated" {
+        resolve_pending_purchase(&payload);
+    }
+    expire_pending_purchases();
+
     crate::listeners::trigger(&event, payload)
         .map_err(|e| ffi::FFIResult::Err(format!("Failed to trigger event '{event}': {e}")))
 }
 
-pub fn init<R: Runtime, C: DeserializeOwned>(
+/// Pending purchases, shared with the FFI `trigger` callback.
+fn pending_purchases() -> &'static PendingPurchases {
+    static PENDING_PURCHASES: OnceLock<PendingPurchases> = OnceLock::new();
+    PENDING_PURCHASES.get_or_init(PendingPurchases::new)
+}
+
+/// Emits `pendingPurchaseCompleted` or `pendingPurchaseDeclined` if the
+/// purchase update resolves a pending purchase.
+fn resolve_pending_purchase(payload: &str) {
+    let Ok(purchase) = serde_json::from_str::<Purchase>(payload) else {
+        return;
+    };
+    let Some((resolution, pending)) = pending_purchases().resolve(&purchase) else {
+        return;
+    };
+
+    let result = match resolution {
+        PendingResolution::Completed => serde_json::to_string(&purchase),
+        PendingResolution::Declined => serde_json::to_string(&pending),
+    }
+    .map_err(|e| crate::error::PluginInvokeError::CannotSerializePayload(e).into())
+    .and_then(|json| crate::listeners::trigger(resolution.event(), json));
+    if let Err(e) = result {
+        log::warn!("Failed to trigger event '{}': {e}", resolution.event());
+    }
+}
+
+/// Emits `pendingPurchaseDeclined` for Ask to Buy requests that expired
+/// without an update.
+fn expire_pending_purchases() {
+    for pending in pending_purchases().expire(crate::time::now_millis()) {
+        let result = serde_json::to_string(&pending)
+            .map_err(|e| crate::error::PluginInvokeError::CannotSerializePayload(e).into())
+            .and_then(|json| crate::listeners::trigger("pendingPurchaseDeclined", json));
+        if let Err(e) = result {
+            log::warn!("Failed to trigger event 'pendingPurchaseDeclined': {e}");
+        }
+    }
+}
+
+pub fn init<R: Runtime>(
     app: &AppHandle<R>,
-    _api: PluginApi<R, C>,
+    api: PluginApi<R, Option<Config>>,
 ) -> crate::Result<Iap<R>> {
+    let config = api.config().clone().unwrap_or_default();
+
     Ok(Iap {
         _app: app.clone(),
         plugin: ffi::IapPlugin::init_plugin(),
+        product_cache: ProductCache::new(config.product_cache_ttl()),
     })
 }
 
@@ -115,6 +191,7 @@ pub fn init<R: Runtime, C: DeserializeOwned>(
 pub struct Iap<R: Runtime> {
     _app: AppHandle<R>,
     plugin: ffi::IapPlugin,
+    product_cache: ProductCache,
 }
 
 impl<R: Runtime> Iap<R> {
@@ -122,26 +199,55 @@ impl<R: Runtime> Iap<R> {
         &self,
         product_ids: Vec<String>,
         product_type: String,
+        refresh: bool,
     ) -> crate::Result<GetProductsResponse> {
         validation::require_bundle()?;
 
-        self.plugin
-            .getProducts(product_ids, product_type)
+        self.product_cache
+            .get_products(
+                product_ids,
+                product_type,
+                refresh,
+                |product_ids, product_type| async move {
+                    self.plugin
+                        .getProducts(product_ids, product_type)
+                        .await
+                        .parse()
+                },
+            )
             .await
-            .parse()
     }
 
     pub async fn purchase(&self, payload: PurchaseRequest) -> crate::Result<Purchase> {
         validation::require_bundle()?;
 
-        self.plugin
+        let (offer_token, account_token) = payload
+            .options
+            .map(|opts| (opts.offer_token, opts.app_account_token))
+            .unwrap_or_default();
+
+        let purchase: Purchase = self
+            .plugin
             .purchase(
                 payload.product_id,
                 payload.product_type,
-                payload.options.and_then(|opts| opts.offer_token),
+                offer_token,
+                account_token,
             )
             .await
-            .parse()
+            .parse()?;
+
+        // Ask to Buy and deferred payments complete through Transaction.updates
+        if purchase.purchase_state == PurchaseStateValue::Pending {
+            pending_purchases().track(PendingPurchase {
+                product_id: purchase.product_id.clone(),
+                account_token: purchase.account_token.clone(),
+                purchase_token: None,
+                pending_since: crate::time::now_millis(),
+            });
+        }
+
+        Ok(purchase)
     }
 
     pub async fn restore_purchases(
@@ -181,9 +287,95 @@ impl<R: Runtime> Iap<R> {
     pub async fn consume_purchase(
         &self,
         purchase_token: String,
+        _quantity: u32,
     ) -> crate::Result<ConsumePurchaseResponse> {
         validation::require_bundle()?;
 
         self.plugin.consumePurchase(purchase_token).await.parse()
     }
+
+    pub async fn get_consumable_balance_remaining(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<ConsumableBalance> {
+        Err(crate::Error::from(std::io::Error::other(
+            "Consumable balances are only available on Windows",
+        )))
+    }
+
+    pub async fn get_storefront(&self) -> crate::Result<Storefront> {
+        validation::require_bundle()?;
+
+        self.plugin.getStorefront().await.parse()
+    }
+
+    pub async fn get_app_license(&self) -> crate::Result<AppLicense> {
+        validation::require_bundle()?;
+
+        self.plugin.getAppLicense().await.parse()
+    }
+
+    pub async fn get_app_transaction(&self, refresh: bool) -> crate::Result<AppTransaction> {
+        validation::require_bundle()?;
+
+        self.plugin.getAppTransaction(refresh).await.parse()
+    }
+
+    pub async fn begin_refund_request(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestResponse> {
+        validation::require_bundle()?;
+
+        self.plugin.beginRefundRequest(transaction_id).await.parse()
+    }
+
+    /// Opens the Mac App Store subscriptions page; unlike iOS, macOS has no
+    /// manage-subscriptions sheet.
+    pub async fn show_manage_subscriptions(
+        &self,
+        _product_id: Option<String>,
+    ) -> crate::Result<()> {
+        let url = crate::links::APP_STORE_SUBSCRIPTIONS_URL;
+        if self.plugin.openUrl(url.to_string()) {
+            Ok(())
+        } else {
+            Err(crate::Error::from(std::io::Error::other(format!(
+                "Failed to open {url}"
+            ))))
+        }
+    }
+
+    /// Redeemed offer codes are reported by `Transaction.updates` as
+    /// `purchaseUpdated` events.
+    pub async fn present_code_redemption(&self) -> crate::Result<()> {
+        validation::require_bundle()?;
+
+        self.plugin
+            .presentOfferCodeRedeemSheet()
+            .await
+            .parse::<serde_json::Value>()
+            .map(|_| ())
+    }
+
+    pub async fn get_pending_purchases(&self) -> crate::Result<GetPendingPurchasesResponse> {
+        expire_pending_purchases();
+        Ok(GetPendingPurchasesResponse {
+            pending_purchases: pending_purchases().list(),
+        })
+    }
+
+    pub async fn capabilities(&self) -> crate::Result<Capabilities> {
+        let prerequisite_error = validation::require_bundle().err().map(|e| e.to_string());
+        // StoreKit must not be called from outside a bundle
+        let can_make_payments = prerequisite_error.is_none() && self.plugin.canMakePayments();
+
+        Ok(Capabilities {
+            backend: StoreBackend::AppStore,
+            supported_operations: SUPPORTED_OPERATIONS.to_vec(),
+            prerequisites_met: prerequisite_error.is_none(),
+            prerequisite_error,
+            can_make_payments,
+        })
+    }
 }
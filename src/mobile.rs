@@ -1,10 +1,11 @@
-use serde::de::DeserializeOwned;
 use tauri::{
     plugin::{PluginApi, PluginHandle},
     AppHandle, Runtime,
 };
 
+use crate::cache::ProductCache;
 use crate::models::*;
+use crate::Config;
 
 #[cfg(target_os = "android")]
 const PLUGIN_IDENTIFIER: &str = "app.tauri.iap";
@@ -12,42 +13,106 @@ const PLUGIN_IDENTIFIER: &str = "app.tauri.iap";
 #[cfg(target_os = "ios")]
 tauri::ios_plugin_binding!(init_plugin_iap);
 
+#[cfg(target_os = "android")]
+const BACKEND: StoreBackend = StoreBackend::GooglePlay;
+#[cfg(target_os = "ios")]
+const BACKEND: StoreBackend = StoreBackend::AppStore;
+
+#[cfg(target_os = "android")]
+const SUPPORTED_OPERATIONS: &[StoreOperation] = &[
+    StoreOperation::GetProducts,
+    StoreOperation::Purchase,
+    StoreOperation::RestorePurchases,
+    StoreOperation::AcknowledgePurchase,
+    StoreOperation::ConsumePurchase,
+    StoreOperation::GetProductStatus,
+    StoreOperation::GetStorefront,
+    StoreOperation::GetAppLicense,
+    StoreOperation::ShowManageSubscriptions,
+    StoreOperation::PresentCodeRedemption,
+    StoreOperation::GetPendingPurchases,
+];
+#[cfg(target_os = "ios")]
+const SUPPORTED_OPERATIONS: &[StoreOperation] = &[
+    StoreOperation::GetProducts,
+    StoreOperation::Purchase,
+    StoreOperation::RestorePurchases,
+    StoreOperation::AcknowledgePurchase,
+    StoreOperation::ConsumePurchase,
+    StoreOperation::GetProductStatus,
+    StoreOperation::GetStorefront,
+    StoreOperation::GetAppLicense,
+    StoreOperation::GetAppTransaction,
+    StoreOperation::BeginRefundRequest,
+    StoreOperation::ShowManageSubscriptions,
+    StoreOperation::PresentCodeRedemption,
+    StoreOperation::GetPendingPurchases,
+];
+
 // initializes the Kotlin or Swift plugin classes
-pub fn init<R: Runtime, C: DeserializeOwned>(
-    _app: &AppHandle<R>,
-    api: PluginApi<R, C>,
+pub fn init<R: Runtime>(
+    app: &AppHandle<R>,
+    api: PluginApi<R, Option<Config>>,
 ) -> crate::Result<Iap<R>> {
+    let config = api.config().clone().unwrap_or_default();
+
     #[cfg(target_os = "android")]
     let handle = api.register_android_plugin(PLUGIN_IDENTIFIER, "IapPlugin")?;
     #[cfg(target_os = "ios")]
     let handle = api.register_ios_plugin(init_plugin_iap)?;
 
-    Ok(Iap(handle))
+    #[cfg(target_os = "ios")]
+    let _ = app;
+
+    Ok(Iap {
+        handle,
+        product_cache: ProductCache::new(config.product_cache_ttl()),
+        #[cfg(target_os = "android")]
+        package_name: app.config().identifier.clone(),
+    })
 }
 
 /// Access to the iap APIs.
-pub struct Iap<R: Runtime>(PluginHandle<R>);
+pub struct Iap<R: Runtime> {
+    handle: PluginHandle<R>,
+    product_cache: ProductCache,
+    /// Application ID, which Tauri derives from the bundle identifier.
+    #[cfg(target_os = "android")]
+    package_name: String,
+}
 
 impl<R: Runtime> Iap<R> {
     pub async fn get_products(
         &self,
         product_ids: Vec<String>,
         product_type: String,
+        refresh: bool,
     ) -> crate::Result<GetProductsResponse> {
-        self.0
-            .run_mobile_plugin_async(
-                "getProducts",
-                GetProductsRequest {
-                    product_ids,
-                    product_type,
+        self.product_cache
+            .get_products(
+                product_ids,
+                product_type,
+                refresh,
+                |product_ids, product_type| async move {
+                    self.handle
+                        .run_mobile_plugin_async(
+                            "getProducts",
+                            GetProductsRequest {
+                                product_ids,
+                                product_type,
+                                refresh,
+                                strict: false,
+                            },
+                        )
+                        .await
+                        .map_err(Into::into)
                 },
             )
             .await
-            .map_err(Into::into)
     }
 
     pub async fn purchase(&self, payload: PurchaseRequest) -> crate::Result<Purchase> {
-        self.0
+        self.handle
             .run_mobile_plugin_async("purchase", payload)
             .await
             .map_err(Into::into)
@@ -57,14 +122,20 @@ impl<R: Runtime> Iap<R> {
         &self,
         product_type: String,
     ) -> crate::Result<RestorePurchasesResponse> {
-        self.0
-            .run_mobile_plugin_async("restorePurchases", RestorePurchasesRequest { product_type })
+        self.handle
+            .run_mobile_plugin_async(
+                "restorePurchases",
+                RestorePurchasesRequest {
+                    product_type,
+                    ownership_type: None,
+                },
+            )
             .await
             .map_err(Into::into)
     }
 
     pub fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
-        self.0
+        self.handle
             .run_mobile_plugin("getPurchaseHistory", ())
             .map_err(Into::into)
     }
@@ -73,7 +144,7 @@ impl<R: Runtime> Iap<R> {
         &self,
         purchase_token: String,
     ) -> crate::Result<AcknowledgePurchaseResponse> {
-        self.0
+        self.handle
             .run_mobile_plugin_async(
                 "acknowledgePurchase",
                 AcknowledgePurchaseRequest { purchase_token },
@@ -87,7 +158,7 @@ impl<R: Runtime> Iap<R> {
         product_id: String,
         product_type: String,
     ) -> crate::Result<ProductStatus> {
-        self.0
+        self.handle
             .run_mobile_plugin_async(
                 "getProductStatus",
                 GetProductStatusRequest {
@@ -102,13 +173,153 @@ impl<R: Runtime> Iap<R> {
     pub async fn consume_purchase(
         &self,
         purchase_token: String,
+        quantity: u32,
     ) -> crate::Result<ConsumePurchaseResponse> {
-        self.0
+        self.handle
             .run_mobile_plugin_async(
                 "consumePurchase",
-                ConsumePurchaseRequest { purchase_token },
+                ConsumePurchaseRequest {
+                    purchase_token,
+                    quantity,
+                },
             )
             .await
             .map_err(Into::into)
     }
+
+    pub async fn get_consumable_balance_remaining(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<ConsumableBalance> {
+        Err(crate::Error::from(std::io::Error::other(
+            "Consumable balances are only available on Windows",
+        )))
+    }
+
+    pub async fn get_storefront(&self) -> crate::Result<Storefront> {
+        self.handle
+            .run_mobile_plugin_async("getStorefront", ())
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_app_license(&self) -> crate::Result<AppLicense> {
+        // Google Play does not license the app itself
+        #[cfg(target_os = "android")]
+        {
+            Ok(AppLicense::not_applicable())
+        }
+
+        #[cfg(target_os = "ios")]
+        {
+            self.handle
+                .run_mobile_plugin_async("getAppLicense", ())
+                .await
+                .map_err(Into::into)
+        }
+    }
+
+    pub async fn get_app_transaction(&self, refresh: bool) -> crate::Result<AppTransaction> {
+        #[cfg(target_os = "android")]
+        {
+            let _ = refresh;
+            Err(crate::Error::from(std::io::Error::other(
+                "App transactions are only available on iOS and macOS",
+            )))
+        }
+
+        #[cfg(target_os = "ios")]
+        {
+            self.handle
+                .run_mobile_plugin_async("getAppTransaction", GetAppTransactionRequest { refresh })
+                .await
+                .map_err(Into::into)
+        }
+    }
+
+    pub async fn begin_refund_request(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestResponse> {
+        // Google Play refunds are only issued through the Play Console or
+        // the Google Play Developer API
+        #[cfg(target_os = "android")]
+        {
+            let _ = transaction_id;
+            Err(crate::Error::from(std::io::Error::other(
+                "Refund requests are only available on iOS and macOS",
+            )))
+        }
+
+        #[cfg(target_os = "ios")]
+        {
+            self.handle
+                .run_mobile_plugin_async("beginRefundRequest", RefundRequest { transaction_id })
+                .await
+                .map_err(Into::into)
+        }
+    }
+
+    pub async fn show_manage_subscriptions(&self, product_id: Option<String>) -> crate::Result<()> {
+        #[cfg(target_os = "android")]
+        let (command, payload) = (
+            "openUrl",
+            OpenUrlRequest {
+                url: crate::links::play_subscriptions_url(
+                    &self.package_name,
+                    product_id.as_deref(),
+                ),
+            },
+        );
+        // StoreKit's sheet always lists all subscriptions
+        #[cfg(target_os = "ios")]
+        let (command, payload) = {
+            let _ = product_id;
+            ("showManageSubscriptions", ())
+        };
+
+        self.handle
+            .run_mobile_plugin_async::<serde_json::Value>(command, payload)
+            .await?;
+        Ok(())
+    }
+
+    /// Redeemed codes are reported as `purchaseUpdated` events.
+    pub async fn present_code_redemption(&self) -> crate::Result<()> {
+        #[cfg(target_os = "android")]
+        let payload = OpenUrlRequest {
+            url: crate::links::PLAY_REDEEM_URL.to_string(),
+        };
+        #[cfg(target_os = "ios")]
+        let payload = ();
+
+        self.handle
+            .run_mobile_plugin_async::<serde_json::Value>("presentCodeRedemption", payload)
+            .await?;
+        Ok(())
+    }
+
+    /// Pending purchases are tracked by the native plugin, which also emits
+    /// `pendingPurchaseCompleted` and `pendingPurchaseDeclined`.
+    pub async fn get_pending_purchases(&self) -> crate::Result<GetPendingPurchasesResponse> {
+        self.handle
+            .run_mobile_plugin_async("getPendingPurchases", ())
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn capabilities(&self) -> crate::Result<Capabilities> {
+        let response: CanMakePaymentsResponse = self
+            .handle
+            .run_mobile_plugin_async("canMakePayments", ())
+            .await?;
+
+        Ok(Capabilities {
+            backend: BACKEND,
+            supported_operations: SUPPORTED_OPERATIONS.to_vec(),
+            prerequisites_met: true,
+            prerequisite_error: None,
+            can_make_payments: response.can_make_payments,
+        })
+    }
 }
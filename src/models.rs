@@ -12,19 +12,398 @@ pub struct GetProductsRequest {
     pub product_ids: Vec<String>,
     #[serde(default = "default_product_type")]
     pub product_type: String,
+    /// Bypass the product cache and query the store.
+    #[serde(default)]
+    pub refresh: bool,
+    /// Fail with [`crate::Error::ProductsNotFound`] if any id is not found.
+    #[serde(default)]
+    pub strict: bool,
 }
 
 fn default_product_type() -> String {
     "subs".to_string()
 }
 
+/// Unit of a [`BillingPeriod`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeriodUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl PeriodUnit {
+    /// Number of whole units in a (non-leap) year.
+    fn per_year(self) -> i128 {
+        match self {
+            PeriodUnit::Day => 365,
+            PeriodUnit::Week => 52,
+            PeriodUnit::Month => 12,
+            PeriodUnit::Year => 1,
+        }
+    }
+
+    fn designator(self) -> char {
+        match self {
+            PeriodUnit::Day => 'D',
+            PeriodUnit::Week => 'W',
+            PeriodUnit::Month => 'M',
+            PeriodUnit::Year => 'Y',
+        }
+    }
+}
+
+/// Error returned when a string is not a supported ISO-8601 billing period.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid billing period: {0:?}")]
+pub struct InvalidBillingPeriod(pub String);
+
+/// Subscription billing period such as `P1M` or `P1Y`.
+///
+/// Serialized as the ISO-8601 duration string used by Google Play and
+/// StoreKit. Only single-component periods (`P<n>D`, `P<n>W`, `P<n>M`,
+/// `P<n>Y`) with a non-zero count are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BillingPeriod {
+    count: u32,
+    unit: PeriodUnit,
+}
+
+impl BillingPeriod {
+    /// Fails if `count` is zero.
+    pub fn new(count: u32, unit: PeriodUnit) -> Result<Self, InvalidBillingPeriod> {
+        if count == 0 {
+            return Err(InvalidBillingPeriod(format!("P0{}", unit.designator())));
+        }
+        Ok(Self { count, unit })
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn unit(&self) -> PeriodUnit {
+        self.unit
+    }
+
+    /// Normalizes a price for this period to a per-month price.
+    ///
+    /// Uses 12 months, 52 weeks and 365 days per year and rounds to the
+    /// nearest micro. Returns `None` if the result does not fit in an `i64`.
+    pub fn price_per_month_micros(&self, amount_micros: i64) -> Option<i64> {
+        self.normalize_micros(amount_micros, PeriodUnit::Month)
+    }
+
+    /// Normalizes a price for this period to a per-week price.
+    pub fn price_per_week_micros(&self, amount_micros: i64) -> Option<i64> {
+        self.normalize_micros(amount_micros, PeriodUnit::Week)
+    }
+
+    /// Normalizes a price for this period to a per-year price.
+    pub fn price_per_year_micros(&self, amount_micros: i64) -> Option<i64> {
+        self.normalize_micros(amount_micros, PeriodUnit::Year)
+    }
+
+    fn normalize_micros(&self, amount_micros: i64, target: PeriodUnit) -> Option<i64> {
+        let numerator = i128::from(amount_micros) * self.unit.per_year();
+        let denominator = i128::from(self.count) * target.per_year();
+        let half = denominator / 2;
+        let rounded = if numerator >= 0 {
+            (numerator + half) / denominator
+        } else {
+            (numerator - half) / denominator
+        };
+        i64::try_from(rounded).ok()
+    }
+
+    /// Approximate length in days, using 7-day weeks, 30-day months and
+    /// 365-day years. Returns `None` on overflow.
+    pub fn approx_days(&self) -> Option<u32> {
+        let days_per_unit = match self.unit {
+            PeriodUnit::Day => 1,
+            PeriodUnit::Week => 7,
+            PeriodUnit::Month => 30,
+            PeriodUnit::Year => 365,
+        };
+        self.count.checked_mul(days_per_unit)
+    }
+
+    /// Approximate length as a [`std::time::Duration`]. See [`Self::approx_days`].
+    pub fn to_duration(&self) -> Option<std::time::Duration> {
+        self.approx_days()
+            .map(|days| std::time::Duration::from_secs(u64::from(days) * 24 * 60 * 60))
+    }
+}
+
+impl std::fmt::Display for BillingPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "P{}{}", self.count, self.unit.designator())
+    }
+}
+
+impl std::str::FromStr for BillingPeriod {
+    type Err = InvalidBillingPeriod;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidBillingPeriod(s.to_string());
+
+        let body = s.strip_prefix('P').ok_or_else(invalid)?;
+        let designator = body.chars().last().ok_or_else(invalid)?;
+        let digits = &body[..body.len() - designator.len_utf8()];
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let unit = match designator {
+            'D' => PeriodUnit::Day,
+            'W' => PeriodUnit::Week,
+            'M' => PeriodUnit::Month,
+            'Y' => PeriodUnit::Year,
+            _ => return Err(invalid()),
+        };
+        let count = digits.parse::<u32>().map_err(|_| invalid())?;
+        BillingPeriod::new(count, unit).map_err(|_| invalid())
+    }
+}
+
+impl Serialize for BillingPeriod {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BillingPeriod {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Error returned when a localized price string cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid price: {0:?}")]
+pub struct InvalidPrice(pub String);
+
+/// A price in micros (amount × 1,000,000) with its ISO 4217 currency code.
+///
+/// Serializes as the `priceAmountMicros` / `priceCurrencyCode` pair used by
+/// the stores, so it can be flattened into [`Product`] and [`PricingPhase`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Money {
+    #[serde(rename = "priceAmountMicros")]
+    pub micros: i64,
+    #[serde(rename = "priceCurrencyCode")]
+    pub currency: String,
+}
+
+impl Money {
+    pub fn new(micros: i64, currency: impl Into<String>) -> Self {
+        Self {
+            micros,
+            currency: currency.into(),
+        }
+    }
+
+    /// Parses a localized price string such as `"$1,234.56"`, `"1.234,56 €"`
+    /// or `"CHF 1'234.50"`.
+    ///
+    /// Currency symbols and codes are ignored; `currency` is used as the
+    /// resulting currency and to disambiguate a lone separator followed by
+    /// three digits (`"1.234"` is 1234 in EUR but 1.234 in KWD).
+    pub fn parse_formatted(
+        formatted: &str,
+        currency: impl Into<String>,
+    ) -> Result<Self, InvalidPrice> {
+        let currency = currency.into();
+        let micros = PriceLayout::parse(formatted, currency_minor_units(&currency))
+            .map(|layout| layout.micros)
+            .ok_or_else(|| InvalidPrice(formatted.to_string()))?;
+        Ok(Self { micros, currency })
+    }
+}
+
+/// Number of minor-unit digits for an ISO 4217 currency code.
+///
+/// Defaults to 2 for codes not listed as zero- or three-decimal currencies.
+pub fn currency_minor_units(currency: &str) -> u32 {
+    match currency.to_ascii_uppercase().as_str() {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Maps ASCII, Arabic-Indic and Extended Arabic-Indic digits to their value.
+fn digit_value(c: char) -> Option<u32> {
+    match c {
+        '0'..='9' => c.to_digit(10),
+        '\u{0660}'..='\u{0669}' => Some(c as u32 - 0x0660),
+        '\u{06F0}'..='\u{06F9}' => Some(c as u32 - 0x06F0),
+        _ => None,
+    }
+}
+
+/// Separators that can only ever group thousands.
+fn is_group_only_separator(c: char) -> bool {
+    matches!(
+        c,
+        ' ' | '\u{00A0}' | '\u{2009}' | '\u{202F}' | '\'' | '\u{2019}' | '\u{066C}'
+    )
+}
+
+/// Separators that can act either as a decimal mark or a group separator.
+fn is_ambiguous_separator(c: char) -> bool {
+    matches!(c, '.' | ',' | '\u{066B}')
+}
+
+/// How a localized price string is laid out, as detected while parsing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PriceLayout {
+    /// Parsed amount in micros.
+    pub micros: i64,
+    /// Text before the first digit (currency symbol or code).
+    pub prefix: String,
+    /// Text after the last digit.
+    pub suffix: String,
+    /// Decimal mark, if the string has a fractional part.
+    pub decimal: Option<char>,
+    /// Thousands separator, if the integer part is grouped.
+    pub group: Option<char>,
+    /// Whether groups of two precede the last group of three ("1,23,456").
+    pub indian_grouping: bool,
+}
+
+impl PriceLayout {
+    pub(crate) fn parse(formatted: &str, minor_units: u32) -> Option<Self> {
+        // Keep only the span between the first and last digit; everything
+        // outside of it is a currency symbol, code or whitespace.
+        let start = formatted.find(|c| digit_value(c).is_some())?;
+        let end = formatted
+            .char_indices()
+            .filter(|(_, c)| digit_value(*c).is_some())
+            .map(|(i, c)| i + c.len_utf8())
+            .next_back()?;
+        let span = &formatted[start..end];
+
+        let mut digits = String::new();
+        let mut separators: Vec<(usize, char)> = Vec::new();
+        for c in span.chars() {
+            if let Some(d) = digit_value(c) {
+                digits.push(char::from_digit(d, 10)?);
+            } else if is_ambiguous_separator(c) || is_group_only_separator(c) {
+                separators.push((digits.len(), c));
+            } else {
+                return None;
+            }
+        }
+
+        // Decide which separator, if any, is the decimal mark.
+        let ambiguous: Vec<(usize, char)> = separators
+            .iter()
+            .copied()
+            .filter(|(_, c)| is_ambiguous_separator(*c))
+            .collect();
+        let decimal = match ambiguous.last() {
+            None => None,
+            Some(&(last_pos, last)) => {
+                let same_kind = ambiguous.iter().filter(|(_, c)| *c == last).count();
+                let mixed = same_kind < ambiguous.len();
+                if same_kind > 1 {
+                    // Repeated separator: all grouping ("1.234.567"), which
+                    // rules out a different ambiguous separator before it.
+                    if mixed {
+                        return None;
+                    }
+                    None
+                } else if mixed || ambiguous.len() < separators.len() {
+                    // Follows other separators ("1.234,56", "1 234,56"): decimal mark.
+                    Some((last_pos, last))
+                } else if digits.len() - last_pos == 3 && minor_units != 3 {
+                    // Lone "1.234" / "1,234": grouping unless the currency has
+                    // three decimals.
+                    None
+                } else {
+                    Some((last_pos, last))
+                }
+            }
+        };
+
+        // Remaining separators group the integer part into threes, allowing
+        // groups of two before the last one for Indian-style "1,23,456".
+        let decimal_at = decimal.map(|(pos, _)| pos);
+        let int_len = decimal_at.unwrap_or(digits.len());
+        let group_separators: Vec<(usize, char)> = separators
+            .iter()
+            .copied()
+            .filter(|sep| Some(*sep) != decimal)
+            .collect();
+        let groups: Vec<usize> = group_separators.iter().map(|(pos, _)| *pos).collect();
+        let mut indian_grouping = false;
+        if let Some(&first) = groups.first() {
+            if !(1..=3).contains(&first) {
+                return None;
+            }
+            let mut bounds = groups.clone();
+            bounds.push(int_len);
+            let gaps: Vec<usize> = bounds
+                .windows(2)
+                .map(|w| w[1].checked_sub(w[0]))
+                .collect::<Option<_>>()?;
+            let (last_gap, inner_gaps) = gaps.split_last()?;
+            if *last_gap != 3 || inner_gaps.iter().any(|gap| !(2..=3).contains(gap)) {
+                return None;
+            }
+            indian_grouping = inner_gaps.contains(&2);
+        }
+
+        let (int_part, frac_part) = match decimal_at {
+            Some(pos) => digits.split_at(pos),
+            None => (digits.as_str(), ""),
+        };
+        if int_part.is_empty() {
+            return None;
+        }
+
+        let mut micros: i64 = 0;
+        for c in int_part.chars() {
+            micros = micros
+                .checked_mul(10)?
+                .checked_add(i64::from(c.to_digit(10)?))?;
+        }
+        micros = micros.checked_mul(1_000_000)?;
+
+        let mut scale = 100_000;
+        for c in frac_part.chars().take(6) {
+            micros = micros.checked_add(i64::from(c.to_digit(10)?) * scale)?;
+            scale /= 10;
+        }
+
+        Some(Self {
+            micros,
+            prefix: formatted[..start].to_string(),
+            suffix: formatted[end..].to_string(),
+            decimal: decimal.map(|(_, c)| c),
+            group: group_separators.first().map(|(_, c)| *c),
+            indian_grouping,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PricingPhase {
     pub formatted_price: String,
-    pub price_currency_code: String,
-    pub price_amount_micros: i64,
-    pub billing_period: String,
+    #[serde(flatten)]
+    pub price: Money,
+    pub billing_period: BillingPeriod,
     pub billing_cycle_count: i32,
     pub recurrence_mode: i32,
 }
@@ -47,10 +426,8 @@ pub struct Product {
     pub product_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub formatted_price: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub price_currency_code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub price_amount_micros: Option<i64>,
+    #[serde(flatten)]
+    pub price: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subscription_offer_details: Option<Vec<SubscriptionOffer>>,
 }
@@ -59,6 +436,82 @@ pub struct Product {
 #[serde(rename_all = "camelCase")]
 pub struct GetProductsResponse {
     pub products: Vec<Product>,
+    /// Requested ids the store did not return (typos, unpublished products).
+    #[serde(default)]
+    pub invalid_product_ids: Vec<String>,
+}
+
+impl GetProductsResponse {
+    /// Builds a response for `requested` ids, listing the ids missing from
+    /// `products` as invalid in request order.
+    pub fn from_requested(requested: &[String], products: Vec<Product>) -> Self {
+        let mut invalid_product_ids: Vec<String> = Vec::new();
+        for id in requested {
+            let found = products.iter().any(|p| &p.product_id == id);
+            if !found && !invalid_product_ids.contains(id) {
+                invalid_product_ids.push(id.clone());
+            }
+        }
+        Self {
+            products,
+            invalid_product_ids,
+        }
+    }
+
+    /// Turns any invalid product ids into an error.
+    pub fn into_strict(self) -> crate::Result<Self> {
+        if self.invalid_product_ids.is_empty() {
+            Ok(self)
+        } else {
+            Err(crate::Error::ProductsNotFound(self.invalid_product_ids))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparePricesRequest {
+    pub products: Vec<Product>,
+    /// Product to compute savings against. Defaults to the offer with the
+    /// highest price per month.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_product_id: Option<String>,
+}
+
+/// A price normalized to a fixed period, formatted like the store price.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodPrice {
+    #[serde(flatten)]
+    pub price: Money,
+    pub formatted_price: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceComparison {
+    pub product_id: String,
+    pub base_plan_id: String,
+    pub offer_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offer_id: Option<String>,
+    pub billing_period: BillingPeriod,
+    #[serde(flatten)]
+    pub price: Money,
+    pub formatted_price: String,
+    pub price_per_week: PeriodPrice,
+    pub price_per_month: PeriodPrice,
+    pub price_per_year: PeriodPrice,
+    /// Whole-percent savings against the reference offer, rounded down.
+    /// `None` for the reference itself or when currencies differ.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub savings_percent: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparePricesResponse {
+    pub comparisons: Vec<PriceComparison>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -90,7 +543,14 @@ pub struct Purchase {
     pub order_id: Option<String>,
     pub package_name: String,
     pub product_id: String,
-    pub purchase_time: i64,
+    /// Unix milliseconds, `None` if the store does not report it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_time: Option<i64>,
+    /// Unix milliseconds at which the entitlement expires, if it does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_trial: Option<bool>,
     pub purchase_token: String,
     pub purchase_state: PurchaseStateValue,
     pub is_auto_renewing: bool,
@@ -100,6 +560,286 @@ pub struct Purchase {
     pub original_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub jws_representation: Option<String>,
+    /// Unix milliseconds at which the store refunded or revoked the purchase.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_time: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<RevocationReason>,
+    /// Account the purchase was made for: the `appAccountToken` on iOS and
+    /// macOS, the `obfuscatedAccountId` on Android.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_token: Option<String>,
+    /// Whether the user bought the purchase or has it through Family Sharing.
+    /// Always `Purchased` outside iOS and macOS.
+    #[serde(default)]
+    pub ownership_type: OwnershipType,
+    /// Store environment the purchase was made in. Always `Production` on
+    /// Windows, where the Store does not tell sandbox purchases apart.
+    #[serde(default)]
+    pub environment: StoreEnvironment,
+    /// Store transaction identifier: the transaction ID on iOS and macOS, the
+    /// order ID on Android. Unlike `purchase_token`, it is unique per renewal.
+    /// Windows reports none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>,
+    /// Identifier of a subscription purchase event across devices (iOS/macOS
+    /// only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_order_line_item_id: Option<String>,
+    /// Number of units purchased.
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+}
+
+impl Purchase {
+    /// Parses `original_json` into the payload of the store that made the
+    /// purchase.
+    ///
+    /// Returns `Ok(None)` if the store provided no payload, e.g. for pending
+    /// purchases.
+    pub fn platform_data(&self) -> Result<Option<PlatformData>, serde_json::Error> {
+        if self.original_json.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let value: serde_json::Value = serde_json::from_str(&self.original_json)?;
+        let data = if value.get("schemaVersion").is_some() {
+            PlatformData::MicrosoftStore(serde_json::from_value(value)?)
+        } else if value.get("bundleId").is_some() {
+            PlatformData::AppStore(serde_json::from_value(value)?)
+        } else if value.get("packageName").is_some() {
+            PlatformData::GooglePlay(serde_json::from_value(value)?)
+        } else {
+            return Err(serde::de::Error::custom(
+                "original_json is not a known store payload",
+            ));
+        };
+        Ok(Some(data))
+    }
+}
+
+/// Typed `Purchase::original_json`, tagged with the store it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "store", rename_all = "camelCase")]
+pub enum PlatformData {
+    GooglePlay(PlayPurchaseData),
+    AppStore(Box<StoreKitTransactionInfo>),
+    MicrosoftStore(WindowsReceipt),
+}
+
+/// Contents of `Purchase::original_json` on Android: Google Play's purchase
+/// data, which `Purchase::signature` signs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayPurchaseData {
+    /// Absent for pending purchases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    pub package_name: String,
+    /// Set for purchases of a single product.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    /// Set for purchases of several products.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_ids: Vec<String>,
+    /// Unix milliseconds.
+    pub purchase_time: i64,
+    /// Play's raw state: `0` purchased, `4` pending. Unlike
+    /// `PurchaseStateValue`, this is not normalized across stores.
+    pub purchase_state: i32,
+    pub purchase_token: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+    /// Absent for one-time products.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_renewing: Option<bool>,
+    #[serde(default)]
+    pub acknowledged: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obfuscated_account_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obfuscated_profile_id: Option<String>,
+    /// `0` for license tester purchases, `1` for promo codes, `2` for
+    /// rewarded products. Absent for regular purchases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_type: Option<i32>,
+}
+
+/// Contents of `Purchase::original_json` on iOS and macOS: StoreKit's
+/// `Transaction.jsonRepresentation`, the decoded payload of
+/// `Purchase::jws_representation`. Times are Unix milliseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreKitTransactionInfo {
+    pub transaction_id: String,
+    pub original_transaction_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_order_line_item_id: Option<String>,
+    pub bundle_id: String,
+    pub product_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_group_identifier: Option<String>,
+    pub purchase_date: i64,
+    pub original_purchase_date: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_date: Option<i64>,
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+    /// `"Auto-Renewable Subscription"`, `"Non-Consumable"`, `"Consumable"` or
+    /// `"Non-Renewing Subscription"`.
+    #[serde(rename = "type")]
+    pub product_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_account_token: Option<String>,
+    /// `"PURCHASED"` or `"FAMILY_SHARED"`.
+    pub in_app_ownership_type: String,
+    pub signed_date: i64,
+    /// `0` for other reasons, `1` for an issue with the app.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_date: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_upgraded: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_type: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_identifier: Option<String>,
+    /// `"Production"`, `"Sandbox"` or `"Xcode"`.
+    pub environment: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storefront: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storefront_id: Option<String>,
+    /// `"PURCHASE"` or `"RENEWAL"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_reason: Option<String>,
+    /// Price in milliunits of `currency`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_verification: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_verification_nonce: Option<String>,
+}
+
+/// A purchase awaiting approval, e.g. Ask to Buy, or a deferred payment.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingPurchase {
+    pub product_id: String,
+    /// Account the purchase was made for, see `Purchase::account_token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_token: Option<String>,
+    /// Store token of the pending purchase. Only Google Play assigns one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_token: Option<String>,
+    /// Unix milliseconds at which the purchase became pending.
+    pub pending_since: i64,
+}
+
+/// An event the plugin emits to apps, tagged with its event name.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "event", content = "payload", rename_all = "camelCase")]
+pub enum IapEvent {
+    PurchaseUpdated(Purchase),
+    PurchaseRevoked(Purchase),
+    PendingPurchaseCompleted(Purchase),
+    PendingPurchaseDeclined(PendingPurchase),
+    StorefrontChanged(Storefront),
+}
+
+impl IapEvent {
+    /// Name apps listen to, e.g. `purchaseUpdated`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PurchaseUpdated(_) => "purchaseUpdated",
+            Self::PurchaseRevoked(_) => "purchaseRevoked",
+            Self::PendingPurchaseCompleted(_) => "pendingPurchaseCompleted",
+            Self::PendingPurchaseDeclined(_) => "pendingPurchaseDeclined",
+            Self::StorefrontChanged(_) => "storefrontChanged",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPendingPurchasesResponse {
+    pub pending_purchases: Vec<PendingPurchase>,
+}
+
+/// Store environment a purchase was made in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreEnvironment {
+    #[default]
+    Production,
+    /// App Store sandbox or a Google Play test purchase.
+    Sandbox,
+    /// StoreKit testing in Xcode.
+    Xcode,
+    /// A purchase made up by the app or its tests, not by a store.
+    Mock,
+}
+
+/// How the user came to own a purchase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OwnershipType {
+    #[default]
+    Purchased,
+    /// Shared by a family member through Family Sharing.
+    FamilyShared,
+}
+
+/// Why the store revoked a purchase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RevocationReason {
+    /// Refunded because of an issue with the app, e.g. content not delivered.
+    DeveloperIssue,
+    /// Refunded or revoked for any other reason, e.g. Family Sharing ended.
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundRequest {
+    /// Store transaction to refund, the `purchaseToken` on iOS and macOS.
+    pub transaction_id: String,
+}
+
+/// Outcome of the refund sheet. A submitted request only reaches Apple; the
+/// refund itself arrives later as a `purchaseRevoked` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RefundRequestStatus {
+    Success,
+    UserCancelled,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundRequestResponse {
+    pub status: RefundRequestStatus,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowManageSubscriptionsRequest {
+    /// Subscription to open, if the store supports it. Otherwise all of the
+    /// user's subscriptions are shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+}
+
+/// Arguments of the native `openUrl` command on Android.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenUrlRequest {
+    pub url: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -107,6 +847,9 @@ pub struct Purchase {
 pub struct RestorePurchasesRequest {
     #[serde(default = "default_product_type")]
     pub product_type: String,
+    /// Only restore purchases with this ownership type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ownership_type: Option<OwnershipType>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -148,12 +891,36 @@ pub struct AcknowledgePurchaseResponse {
 #[serde(rename_all = "camelCase")]
 pub struct ConsumePurchaseRequest {
     pub purchase_token: String,
+    /// Units of a Store-managed consumable to fulfill (Windows only).
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+}
+
+fn default_quantity() -> u32 {
+    1
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConsumePurchaseResponse {
     pub success: bool,
+    /// Units left after fulfillment, for Store-managed consumables on Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_remaining: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetConsumableBalanceRequest {
+    pub product_id: String,
+}
+
+/// Remaining units of a Store-managed consumable.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumableBalance {
+    pub product_id: String,
+    pub balance_remaining: u32,
 }
 
 /// Keep in sync with PurchaseState in guest-js/index.ts
@@ -209,18 +976,275 @@ pub struct ProductStatus {
     pub purchase_time: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expiration_time: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_trial: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_auto_renewing: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_acknowledged: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub purchase_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ownership_type: Option<OwnershipType>,
+}
+
+/// The store region the user purchases from.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Storefront {
+    /// ISO 3166-1 alpha-3 country code, e.g. `"USA"`.
+    pub country_code: String,
+    /// Store-specific storefront identifier. Only the App Store provides one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// ISO 4217 currency code prices are charged in, if the store reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_code: Option<String>,
+}
+
+/// Store backend the plugin is running against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreBackend {
+    AppStore,
+    GooglePlay,
+    MicrosoftStore,
+    /// No store is available on this platform.
+    Unsupported,
+}
+
+/// A plugin operation, named after its JavaScript function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreOperation {
+    GetProducts,
+    Purchase,
+    RestorePurchases,
+    AcknowledgePurchase,
+    ConsumePurchase,
+    GetProductStatus,
+    GetStorefront,
+    GetConsumableBalanceRemaining,
+    GetAppLicense,
+    GetAppTransaction,
+    BeginRefundRequest,
+    ShowManageSubscriptions,
+    PresentCodeRedemption,
+    GetPendingPurchases,
+}
+
+/// What the store backend can do in the current environment.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub backend: StoreBackend,
+    pub supported_operations: Vec<StoreOperation>,
+    /// Whether the app meets the backend's runtime requirements, such as
+    /// running from a signed `.app` bundle on macOS.
+    pub prerequisites_met: bool,
+    /// Why the prerequisites are not met.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prerequisite_error: Option<String>,
+    /// Whether the user may make payments, e.g. not restricted by parental
+    /// controls.
+    pub can_make_payments: bool,
+}
+
+/// Response of the native `canMakePayments` command on mobile.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanMakePaymentsResponse {
+    pub can_make_payments: bool,
+}
+
+/// License of the app itself, as opposed to its in-app products.
+///
+/// Windows reports the Store app license, including trials. The App Store
+/// reports the app transaction of the user's download. Google Play has no
+/// app license and returns [`AppLicense::not_applicable`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLicense {
+    /// Whether the store licenses the app. All other fields are unset if not.
+    pub is_applicable: bool,
+    /// Whether the license is valid, e.g. verified on Apple platforms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_active: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_trial: Option<bool>,
+    /// Seconds left in the trial. Windows only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trial_time_remaining_secs: Option<i64>,
+    /// Identifies the trial across reinstalls on the same account. Windows only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trial_unique_id: Option<String>,
+    /// Unix milliseconds at which the license expires, if it does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<i64>,
+    /// App version the user originally purchased or downloaded. Apple only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_app_version: Option<String>,
+    /// Unix milliseconds of the original purchase or download. Apple only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_purchase_time: Option<i64>,
+    /// Signed app transaction for server-side validation. Apple only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jws_representation: Option<String>,
+}
+
+impl AppLicense {
+    /// Marker for stores without an app license.
+    pub fn not_applicable() -> Self {
+        Self {
+            is_applicable: false,
+            is_active: None,
+            is_trial: None,
+            trial_time_remaining_secs: None,
+            trial_unique_id: None,
+            expiration_time: None,
+            original_app_version: None,
+            original_purchase_time: None,
+            jws_representation: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAppTransactionRequest {
+    /// Fetch a new app transaction from the App Store. This may ask the user
+    /// to sign in, so only set it in response to a user action.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// The App Store's signed record of the user's purchase or download of the
+/// app, verified by StoreKit on the device like every other transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppTransaction {
+    pub bundle_id: String,
+    /// Version of the running app.
+    pub app_version: String,
+    /// App version the user originally purchased or downloaded. This is
+    /// `CFBundleVersion` on iOS and `CFBundleShortVersionString` on macOS.
+    pub original_app_version: String,
+    /// Unix milliseconds of the original purchase or download.
+    pub original_purchase_time: i64,
+    /// Unix milliseconds of the pre-order, if the user pre-ordered the app.
+    /// Only reported on iOS 17.2 and macOS 14.2 or later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preorder_time: Option<i64>,
+    pub environment: StoreEnvironment,
+    /// Unix milliseconds at which the App Store signed the transaction.
+    pub signed_time: i64,
+    /// Base64 SHA-384 hash binding the transaction to this device.
+    pub device_verification: String,
+    pub device_verification_nonce: String,
+    /// Signed app transaction for server-side validation.
+    pub jws_representation: String,
+}
+
+/// Contents of `Purchase::original_json` on Windows.
+///
+/// This is a stable schema for server-side parsing: fields are only ever
+/// added, and `schemaVersion` is bumped if one changes meaning. Times are
+/// Unix milliseconds and absent when the Store does not report them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowsReceipt {
+    pub schema_version: u32,
+    /// Product identifier the purchase was made or restored for.
+    pub product_id: String,
+    /// Store ID of the purchased SKU, `<productStoreId>/<skuId>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku_store_id: Option<String>,
+    /// Outcome of the purchase request. Absent for restored purchases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<WindowsPurchaseStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended_error: Option<WindowsExtendedError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<WindowsLicenseDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<WindowsCollectionData>,
+}
+
+impl WindowsReceipt {
+    pub const SCHEMA_VERSION: u32 = 1;
+
+    pub fn new(product_id: impl Into<String>) -> Self {
+        Self {
+            schema_version: Self::SCHEMA_VERSION,
+            product_id: product_id.into(),
+            sku_store_id: None,
+            status: None,
+            extended_error: None,
+            license: None,
+            collection: None,
+        }
+    }
+}
+
+/// `StorePurchaseStatus` of a Windows purchase request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowsPurchaseStatus {
+    Succeeded,
+    AlreadyPurchased,
+    NotPurchased,
+    NetworkError,
+    ServerError,
+}
+
+/// Extended error the Store attached to a result.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowsExtendedError {
+    /// The `HRESULT` of the error.
+    pub code: i32,
+    pub message: String,
+}
+
+/// The add-on's `StoreLicense`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowsLicenseDetails {
+    pub is_active: bool,
+    /// Absent for perpetual licenses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<i64>,
+    pub in_app_offer_token: String,
+}
+
+/// The SKU's `StoreCollectionData` from the user's collection.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowsCollectionData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acquired_date: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<i64>,
+    pub is_trial: bool,
+    /// Seconds left in the trial, if the SKU is a trial.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trial_time_remaining_secs: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub campaign_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub developer_offer_id: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn period(count: u32, unit: PeriodUnit) -> BillingPeriod {
+        BillingPeriod::new(count, unit).expect("Invalid billing period")
+    }
+
     #[test]
     fn test_default_product_type() {
         assert_eq!(default_product_type(), "subs");
@@ -314,6 +1338,8 @@ mod tests {
             serde_json::from_str(json).expect("Failed to deserialize GetProductsRequest");
         assert_eq!(request.product_ids, vec!["product1", "product2"]);
         assert_eq!(request.product_type, "subs");
+        assert!(!request.refresh);
+        assert!(!request.strict);
     }
 
     #[test]
@@ -332,8 +1358,7 @@ mod tests {
             description: "A test product".to_string(),
             product_type: "inapp".to_string(),
             formatted_price: None,
-            price_currency_code: None,
-            price_amount_micros: None,
+            price: None,
             subscription_offer_details: None,
         };
         let json = serde_json::to_string(&product).expect("Failed to serialize Product");
@@ -351,8 +1376,7 @@ mod tests {
             description: "A test product".to_string(),
             product_type: "inapp".to_string(),
             formatted_price: Some("$9.99".to_string()),
-            price_currency_code: Some("USD".to_string()),
-            price_amount_micros: Some(9990000),
+            price: Some(Money::new(9990000, "USD")),
             subscription_offer_details: None,
         };
         let json = serde_json::to_string(&product).expect("Failed to serialize Product");
@@ -367,7 +1391,9 @@ mod tests {
             order_id: Some("order123".to_string()),
             package_name: "com.example.app".to_string(),
             product_id: "product1".to_string(),
-            purchase_time: 1700000000000,
+            purchase_time: Some(1700000000000),
+            expiration_time: None,
+            is_trial: None,
             purchase_token: "token123".to_string(),
             purchase_state: PurchaseStateValue::Purchased,
             is_auto_renewing: true,
@@ -376,6 +1402,14 @@ mod tests {
             signature: "sig".to_string(),
             original_id: None,
             jws_representation: Some("test_jws".to_string()),
+            revocation_time: None,
+            revocation_reason: None,
+            account_token: None,
+            ownership_type: OwnershipType::FamilyShared,
+            environment: StoreEnvironment::Sandbox,
+            transaction_id: Some("2000000123456789".to_string()),
+            web_order_line_item_id: Some("2000000012345678".to_string()),
+            quantity: 1,
         };
 
         let json = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
@@ -387,15 +1421,51 @@ mod tests {
         assert_eq!(deserialized.purchase_time, purchase.purchase_time);
         assert_eq!(deserialized.purchase_state, purchase.purchase_state);
         assert_eq!(deserialized.is_auto_renewing, purchase.is_auto_renewing);
+        assert_eq!(deserialized.ownership_type, OwnershipType::FamilyShared);
+        assert_eq!(deserialized.environment, StoreEnvironment::Sandbox);
+        assert_eq!(deserialized.transaction_id, purchase.transaction_id);
+        assert_eq!(
+            deserialized.web_order_line_item_id,
+            purchase.web_order_line_item_id
+        );
+    }
+
+    #[test]
+    fn test_purchase_unknown_times() {
+        let json = r#"{
+            "orderId": null,
+            "packageName": "com.example.app",
+            "productId": "product1",
+            "purchaseToken": "token123",
+            "purchaseState": 0,
+            "isAutoRenewing": false,
+            "isAcknowledged": true,
+            "originalJson": "{}",
+            "signature": "",
+            "originalId": null
+        }"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.purchase_time, None);
+        assert_eq!(purchase.expiration_time, None);
+        assert_eq!(purchase.is_trial, None);
+        assert_eq!(purchase.ownership_type, OwnershipType::Purchased);
+        assert_eq!(purchase.environment, StoreEnvironment::Production);
+        assert_eq!(purchase.transaction_id, None);
+        assert_eq!(purchase.quantity, 1);
+
+        let json = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(!json.contains("purchaseTime"));
+        assert!(!json.contains("expirationTime"));
+        assert!(!json.contains("isTrial"));
     }
 
     #[test]
     fn test_pricing_phase_serde() {
         let phase = PricingPhase {
             formatted_price: "$4.99".to_string(),
-            price_currency_code: "USD".to_string(),
-            price_amount_micros: 4990000,
-            billing_period: "P1M".to_string(),
+            price: Money::new(4990000, "USD"),
+            billing_period: period(1, PeriodUnit::Month),
             billing_cycle_count: 1,
             recurrence_mode: 1,
         };
@@ -406,7 +1476,7 @@ mod tests {
 
         let deserialized: PricingPhase =
             serde_json::from_str(&json).expect("Failed to deserialize PricingPhase");
-        assert_eq!(deserialized.price_amount_micros, 4990000);
+        assert_eq!(deserialized.price, Money::new(4990000, "USD"));
     }
 
     #[test]
@@ -417,9 +1487,8 @@ mod tests {
             offer_id: Some("offer1".to_string()),
             pricing_phases: vec![PricingPhase {
                 formatted_price: "$9.99".to_string(),
-                price_currency_code: "USD".to_string(),
-                price_amount_micros: 9990000,
-                billing_period: "P1M".to_string(),
+                price: Money::new(9990000, "USD"),
+                billing_period: period(1, PeriodUnit::Month),
                 billing_cycle_count: 0,
                 recurrence_mode: 1,
             }],
@@ -463,9 +1532,11 @@ mod tests {
             purchase_state: None,
             purchase_time: None,
             expiration_time: None,
+            is_trial: None,
             is_auto_renewing: None,
             is_acknowledged: None,
             purchase_token: None,
+            ownership_type: None,
         };
 
         let json = serde_json::to_string(&status).expect("Failed to serialize ProductStatus");
@@ -473,6 +1544,7 @@ mod tests {
         assert!(!json.contains("purchaseState"));
         assert!(!json.contains("purchaseTime"));
         assert!(!json.contains("expirationTime"));
+        assert!(!json.contains("isTrial"));
     }
 
     #[test]
@@ -483,13 +1555,16 @@ mod tests {
             purchase_state: Some(PurchaseStateValue::Purchased),
             purchase_time: Some(1700000000000),
             expiration_time: Some(1703000000000),
+            is_trial: Some(false),
             is_auto_renewing: Some(true),
             is_acknowledged: Some(true),
             purchase_token: Some("token123".to_string()),
+            ownership_type: Some(OwnershipType::FamilyShared),
         };
 
         let json = serde_json::to_string(&status).expect("Failed to serialize ProductStatus");
         assert!(json.contains(r#""isOwned":true"#));
+        assert!(json.contains(r#""ownershipType":"familyShared""#));
         assert!(json.contains(r#""purchaseState":0"#));
         assert!(json.contains(r#""isAutoRenewing":true"#));
     }
@@ -530,4 +1605,558 @@ mod tests {
             serde_json::from_str(&json).expect("Failed to deserialize PurchaseHistoryRecord");
         assert_eq!(deserialized.quantity, 1);
     }
+
+    #[test]
+    fn test_billing_period_parse() {
+        let cases = [
+            ("P1D", 1, PeriodUnit::Day),
+            ("P1W", 1, PeriodUnit::Week),
+            ("P3M", 3, PeriodUnit::Month),
+            ("P1Y", 1, PeriodUnit::Year),
+        ];
+        for (input, count, unit) in cases {
+            let period: BillingPeriod = input.parse().expect("Failed to parse billing period");
+            assert_eq!(
+                period,
+                BillingPeriod::new(count, unit).expect("Invalid billing period")
+            );
+            assert_eq!(period.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_billing_period_rejects_zero_count() {
+        assert_eq!(
+            BillingPeriod::new(0, PeriodUnit::Month),
+            Err(InvalidBillingPeriod("P0M".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_billing_period_parse_invalid() {
+        for input in [
+            "", "P", "1M", "PM", "P0M", "P1H", "P1Y6M", "PT1H", "P-1M", "p1m",
+        ] {
+            let result = input.parse::<BillingPeriod>();
+            assert_eq!(result, Err(InvalidBillingPeriod(input.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_billing_period_serde() {
+        let period = period(6, PeriodUnit::Month);
+        let json = serde_json::to_string(&period).expect("Failed to serialize BillingPeriod");
+        assert_eq!(json, r#""P6M""#);
+
+        let deserialized: BillingPeriod =
+            serde_json::from_str(&json).expect("Failed to deserialize BillingPeriod");
+        assert_eq!(deserialized, period);
+
+        let err = serde_json::from_str::<BillingPeriod>(r#""P1Q""#)
+            .expect_err("Expected error for invalid billing period")
+            .to_string();
+        assert!(err.contains("Invalid billing period"));
+    }
+
+    #[test]
+    fn test_pricing_phase_rejects_invalid_billing_period() {
+        let json = r#"{"formattedPrice":"$4.99","priceCurrencyCode":"USD","priceAmountMicros":4990000,"billingPeriod":"monthly","billingCycleCount":1,"recurrenceMode":1}"#;
+        assert!(serde_json::from_str::<PricingPhase>(json).is_err());
+    }
+
+    #[test]
+    fn test_billing_period_normalized_prices() {
+        let yearly = period(1, PeriodUnit::Year);
+        assert_eq!(yearly.price_per_month_micros(49_990_000), Some(4_165_833));
+        assert_eq!(yearly.price_per_year_micros(49_990_000), Some(49_990_000));
+
+        let quarterly = period(3, PeriodUnit::Month);
+        assert_eq!(
+            quarterly.price_per_month_micros(12_000_000),
+            Some(4_000_000)
+        );
+        assert_eq!(
+            quarterly.price_per_year_micros(12_000_000),
+            Some(48_000_000)
+        );
+
+        let weekly = period(1, PeriodUnit::Week);
+        assert_eq!(weekly.price_per_week_micros(1_990_000), Some(1_990_000));
+        assert_eq!(weekly.price_per_month_micros(1_990_000), Some(8_623_333));
+
+        let monthly = period(1, PeriodUnit::Month);
+        assert_eq!(monthly.price_per_week_micros(5_200_000), Some(1_200_000));
+    }
+
+    #[test]
+    fn test_billing_period_normalization_overflow() {
+        let daily = period(1, PeriodUnit::Day);
+        assert_eq!(daily.price_per_year_micros(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_billing_period_duration() {
+        assert_eq!(period(2, PeriodUnit::Week).approx_days(), Some(14));
+        assert_eq!(period(1, PeriodUnit::Year).approx_days(), Some(365));
+        assert_eq!(
+            period(1, PeriodUnit::Month).to_duration(),
+            Some(std::time::Duration::from_secs(30 * 24 * 60 * 60))
+        );
+        assert_eq!(period(u32::MAX, PeriodUnit::Year).approx_days(), None);
+    }
+
+    #[test]
+    fn test_money_serde_field_names() {
+        let money = Money::new(9_990_000, "USD");
+        let json = serde_json::to_string(&money).expect("Failed to serialize Money");
+        assert_eq!(
+            json,
+            r#"{"priceAmountMicros":9990000,"priceCurrencyCode":"USD"}"#
+        );
+    }
+
+    #[test]
+    fn test_product_price_missing_deserializes_to_none() {
+        let json = r#"{"productId":"p","title":"t","description":"d","productType":"inapp"}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+        assert_eq!(product.price, None);
+
+        let json = r#"{"productId":"p","title":"t","description":"d","productType":"inapp","priceAmountMicros":990000,"priceCurrencyCode":"EUR"}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+        assert_eq!(product.price, Some(Money::new(990_000, "EUR")));
+    }
+
+    #[test]
+    fn test_money_parse_formatted_locales() {
+        let cases = [
+            // en-US, en-GB, en-IN
+            ("$9.99", "USD", 9_990_000),
+            ("$1,234.56", "USD", 1_234_560_000),
+            ("US$1,234,567.89", "USD", 1_234_567_890_000),
+            ("£0.79", "GBP", 790_000),
+            ("₹1,23,456.00", "INR", 123_456_000_000),
+            // de-DE, nl-NL, it-IT, es-ES, pt-BR
+            ("1.234,56 €", "EUR", 1_234_560_000),
+            ("9,99 €", "EUR", 9_990_000),
+            ("€ 9,99", "EUR", 9_990_000),
+            ("1.234 €", "EUR", 1_234_000_000),
+            ("R$ 1.234,56", "BRL", 1_234_560_000),
+            // fr-FR (narrow no-break space), fr-CA, sv-SE (no-break space)
+            ("1\u{202F}234,56\u{00A0}€", "EUR", 1_234_560_000),
+            ("1 234,56 $", "CAD", 1_234_560_000),
+            ("1\u{00A0}234,00\u{00A0}kr", "SEK", 1_234_000_000),
+            // de-CH
+            ("CHF 1'234.50", "CHF", 1_234_500_000),
+            ("CHF 1\u{2019}234.50", "CHF", 1_234_500_000),
+            // Zero-decimal currencies
+            ("¥1,234", "JPY", 1_234_000_000),
+            ("￥120", "JPY", 120_000_000),
+            ("₩1,200", "KRW", 1_200_000_000),
+            ("1.234 ₫", "VND", 1_234_000_000),
+            ("25.000 ₫", "VND", 25_000_000_000),
+            // Three-decimal currencies
+            ("KWD 1.234", "KWD", 1_234_000),
+            ("1,234.500 د.ك", "KWD", 1_234_500_000),
+            // Arabic-Indic digits and separators
+            ("١٢٣٫٤٥ ر.س", "SAR", 123_450_000),
+            ("١٬٢٣٤٫٥٠ ر.س", "SAR", 1_234_500_000),
+            // Currency code instead of symbol, no decimals
+            ("USD 10", "USD", 10_000_000),
+            ("0,99", "EUR", 990_000),
+        ];
+        for (input, currency, expected) in cases {
+            let money = Money::parse_formatted(input, currency)
+                .unwrap_or_else(|e| panic!("Failed to parse {input:?}: {e}"));
+            assert_eq!(money, Money::new(expected, currency), "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_money_parse_formatted_invalid() {
+        for input in ["", "Free", "€", "1.2.3,4,5", "12,34.56", "1,,234", "1-2"] {
+            let result = Money::parse_formatted(input, "EUR");
+            assert_eq!(
+                result,
+                Err(InvalidPrice(input.to_string())),
+                "input: {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_money_parse_formatted_overflow() {
+        assert!(Money::parse_formatted("$99,999,999,999,999,999", "USD").is_err());
+    }
+
+    #[test]
+    fn test_currency_minor_units() {
+        assert_eq!(currency_minor_units("USD"), 2);
+        assert_eq!(currency_minor_units("jpy"), 0);
+        assert_eq!(currency_minor_units("KWD"), 3);
+    }
+
+    #[test]
+    fn test_get_products_response_invalid_ids() {
+        let requested = vec![
+            "found".to_string(),
+            "typo".to_string(),
+            "unpublished".to_string(),
+            "typo".to_string(),
+        ];
+        let product = Product {
+            product_id: "found".to_string(),
+            title: "Found".to_string(),
+            description: String::new(),
+            product_type: "inapp".to_string(),
+            formatted_price: None,
+            price: None,
+            subscription_offer_details: None,
+        };
+
+        let response = GetProductsResponse::from_requested(&requested, vec![product]);
+        assert_eq!(response.products.len(), 1);
+        assert_eq!(response.invalid_product_ids, vec!["typo", "unpublished"]);
+
+        let err = response
+            .into_strict()
+            .expect_err("Expected strict mode to fail");
+        assert!(
+            matches!(err, crate::Error::ProductsNotFound(ids) if ids == vec!["typo", "unpublished"])
+        );
+    }
+
+    #[test]
+    fn test_get_products_response_strict_ok() {
+        let response = GetProductsResponse::from_requested(&[], Vec::new());
+        assert!(response.into_strict().is_ok());
+    }
+
+    #[test]
+    fn test_get_products_response_missing_invalid_ids_defaults() {
+        let response: GetProductsResponse = serde_json::from_str(r#"{"products":[]}"#)
+            .expect("Failed to deserialize GetProductsResponse");
+        assert!(response.invalid_product_ids.is_empty());
+    }
+
+    #[test]
+    fn test_storefront_deserialize_country_only() {
+        let storefront: Storefront = serde_json::from_str(r#"{"countryCode":"DEU"}"#)
+            .expect("Failed to deserialize Storefront");
+        assert_eq!(storefront.country_code, "DEU");
+        assert_eq!(storefront.id, None);
+        assert_eq!(storefront.currency_code, None);
+        assert_eq!(
+            serde_json::to_string(&storefront).expect("Failed to serialize Storefront"),
+            r#"{"countryCode":"DEU"}"#
+        );
+    }
+
+    #[test]
+    fn test_capabilities_serialize() {
+        let capabilities = Capabilities {
+            backend: StoreBackend::MicrosoftStore,
+            supported_operations: vec![StoreOperation::GetProducts, StoreOperation::GetStorefront],
+            prerequisites_met: false,
+            prerequisite_error: Some("No window".to_string()),
+            can_make_payments: false,
+        };
+        let json: serde_json::Value =
+            serde_json::to_value(&capabilities).expect("Failed to serialize Capabilities");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "backend": "microsoftStore",
+                "supportedOperations": ["getProducts", "getStorefront"],
+                "prerequisitesMet": false,
+                "prerequisiteError": "No window",
+                "canMakePayments": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_consume_purchase_request_default_quantity() {
+        let request: ConsumePurchaseRequest = serde_json::from_str(r#"{"purchaseToken":"token"}"#)
+            .expect("Failed to deserialize ConsumePurchaseRequest");
+        assert_eq!(request.quantity, 1);
+    }
+
+    #[test]
+    fn test_windows_receipt_schema() {
+        let receipt = WindowsReceipt {
+            sku_store_id: Some("9NBLGGH4TNNR/0010".to_string()),
+            status: Some(WindowsPurchaseStatus::Succeeded),
+            extended_error: Some(WindowsExtendedError {
+                code: -2147023673,
+                message: r#"The "operation" was canceled"#.to_string(),
+            }),
+            license: Some(WindowsLicenseDetails {
+                is_active: true,
+                expiration_date: None,
+                in_app_offer_token: "premium".to_string(),
+            }),
+            collection: Some(WindowsCollectionData {
+                acquired_date: Some(1700000000000),
+                start_date: Some(1700000000000),
+                end_date: Some(1702592000000),
+                is_trial: false,
+                trial_time_remaining_secs: None,
+                campaign_id: None,
+                developer_offer_id: None,
+            }),
+            ..WindowsReceipt::new("premium")
+        };
+
+        let json = serde_json::to_string(&receipt).expect("Failed to serialize WindowsReceipt");
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"schemaVersion":1,"productId":"premium","skuStoreId":"9NBLGGH4TNNR/0010","#,
+                r#""status":"succeeded","#,
+                r#""extendedError":{"code":-2147023673,"message":"The \"operation\" was canceled"},"#,
+                r#""license":{"isActive":true,"inAppOfferToken":"premium"},"#,
+                r#""collection":{"acquiredDate":1700000000000,"startDate":1700000000000,"#,
+                r#""endDate":1702592000000,"isTrial":false}}"#
+            )
+        );
+
+        let deserialized: WindowsReceipt =
+            serde_json::from_str(&json).expect("Failed to deserialize WindowsReceipt");
+        assert_eq!(deserialized, receipt);
+    }
+
+    #[test]
+    fn test_windows_receipt_minimal() {
+        let json = serde_json::to_string(&WindowsReceipt::new("premium"))
+            .expect("Failed to serialize WindowsReceipt");
+        assert_eq!(json, r#"{"schemaVersion":1,"productId":"premium"}"#);
+    }
+
+    #[test]
+    fn test_app_license_not_applicable() {
+        let json = serde_json::to_string(&AppLicense::not_applicable())
+            .expect("Failed to serialize AppLicense");
+        assert_eq!(json, r#"{"isApplicable":false}"#);
+    }
+
+    #[test]
+    fn test_app_license_deserialize() {
+        let json = r#"{
+            "isApplicable": true,
+            "isActive": true,
+            "originalAppVersion": "1.0",
+            "originalPurchaseTime": 1600000000000,
+            "jwsRepresentation": "header.payload.signature"
+        }"#;
+        let license: AppLicense =
+            serde_json::from_str(json).expect("Failed to deserialize AppLicense");
+        assert_eq!(license.is_active, Some(true));
+        assert_eq!(license.is_trial, None);
+        assert_eq!(license.original_app_version.as_deref(), Some("1.0"));
+        assert_eq!(license.original_purchase_time, Some(1600000000000));
+    }
+
+    #[test]
+    fn test_app_transaction_deserialize() {
+        let json = r#"{
+            "bundleId": "com.example.app",
+            "appVersion": "2.0",
+            "originalAppVersion": "1.0",
+            "originalPurchaseTime": 1600000000000,
+            "environment": "production",
+            "signedTime": 1700000000000,
+            "deviceVerification": "c2hhMzg0",
+            "deviceVerificationNonce": "5D6E3F0A-1B2C-4D5E-8F90-A1B2C3D4E5F6",
+            "jwsRepresentation": "header.payload.signature"
+        }"#;
+        let transaction: AppTransaction =
+            serde_json::from_str(json).expect("Failed to deserialize AppTransaction");
+        assert_eq!(transaction.original_app_version, "1.0");
+        assert_eq!(transaction.original_purchase_time, 1600000000000);
+        assert_eq!(transaction.preorder_time, None);
+        assert_eq!(transaction.environment, StoreEnvironment::Production);
+    }
+
+    #[test]
+    fn test_get_app_transaction_request_defaults() {
+        let request: GetAppTransactionRequest =
+            serde_json::from_str("{}").expect("Failed to deserialize GetAppTransactionRequest");
+        assert!(!request.refresh);
+    }
+
+    #[test]
+    fn test_purchase_revocation_fields() {
+        let json = r#"{
+            "orderId": "2000000123456789",
+            "packageName": "com.example.app",
+            "productId": "product1",
+            "purchaseTime": 1700000000000,
+            "purchaseToken": "2000000123456789",
+            "purchaseState": 1,
+            "isAutoRenewing": false,
+            "isAcknowledged": true,
+            "originalJson": "",
+            "signature": "",
+            "originalId": "2000000123456789",
+            "revocationTime": 1700100000000,
+            "revocationReason": "developerIssue"
+        }"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.purchase_state, PurchaseStateValue::Canceled);
+        assert_eq!(purchase.revocation_time, Some(1700100000000));
+        assert_eq!(
+            purchase.revocation_reason,
+            Some(RevocationReason::DeveloperIssue)
+        );
+    }
+
+    #[test]
+    fn test_refund_request_response_serde() {
+        let response: RefundRequestResponse = serde_json::from_str(r#"{"status":"userCancelled"}"#)
+            .expect("Failed to deserialize RefundRequestResponse");
+        assert_eq!(response.status, RefundRequestStatus::UserCancelled);
+    }
+
+    #[test]
+    fn test_pending_purchase_serde() {
+        let pending = PendingPurchase {
+            product_id: "premium".to_string(),
+            account_token: None,
+            purchase_token: None,
+            pending_since: 1700000000000,
+        };
+        let json = serde_json::to_string(&GetPendingPurchasesResponse {
+            pending_purchases: vec![pending],
+        })
+        .expect("Failed to serialize GetPendingPurchasesResponse");
+        assert_eq!(
+            json,
+            r#"{"pendingPurchases":[{"productId":"premium","pendingSince":1700000000000}]}"#
+        );
+    }
+
+    #[test]
+    fn test_ownership_type_serde() {
+        assert_eq!(
+            serde_json::to_string(&OwnershipType::FamilyShared)
+                .expect("Failed to serialize OwnershipType"),
+            r#""familyShared""#
+        );
+
+        let request: RestorePurchasesRequest =
+            serde_json::from_str(r#"{"productType":"inapp","ownershipType":"purchased"}"#)
+                .expect("Failed to deserialize RestorePurchasesRequest");
+        assert_eq!(request.ownership_type, Some(OwnershipType::Purchased));
+    }
+
+    fn purchase_with_original_json(original_json: &str) -> Purchase {
+        let mut purchase: Purchase = serde_json::from_str(
+            r#"{
+                "packageName": "com.example.app",
+                "productId": "premium_monthly",
+                "purchaseToken": "token",
+                "purchaseState": 0,
+                "isAutoRenewing": false,
+                "isAcknowledged": true,
+                "originalJson": "",
+                "signature": "",
+                "originalId": null
+            }"#,
+        )
+        .expect("Failed to deserialize Purchase");
+        purchase.original_json = original_json.to_string();
+        purchase
+    }
+
+    #[test]
+    fn test_platform_data_google_play() {
+        let purchase = purchase_with_original_json(include_str!("fixtures/play_purchase.json"));
+        let Some(PlatformData::GooglePlay(data)) = purchase
+            .platform_data()
+            .expect("Failed to parse Play purchase data")
+        else {
+            panic!("Expected Google Play purchase data");
+        };
+        assert_eq!(data.order_id.as_deref(), Some("GPA.3372-5117-6393-28412"));
+        assert_eq!(data.product_id.as_deref(), Some("premium_monthly"));
+        assert_eq!(data.purchase_state, 0);
+        assert_eq!(data.auto_renewing, Some(true));
+        assert!(!data.acknowledged);
+        assert_eq!(data.obfuscated_account_id.as_deref(), Some("a1b2c3d4e5"));
+        assert_eq!(data.purchase_type, None);
+
+        let purchase =
+            purchase_with_original_json(include_str!("fixtures/play_purchase_test.json"));
+        let Some(PlatformData::GooglePlay(data)) = purchase
+            .platform_data()
+            .expect("Failed to parse Play purchase data")
+        else {
+            panic!("Expected Google Play purchase data");
+        };
+        assert_eq!(data.purchase_type, Some(0));
+        assert_eq!(data.quantity, 3);
+        assert_eq!(data.auto_renewing, None);
+    }
+
+    #[test]
+    fn test_platform_data_app_store() {
+        let purchase =
+            purchase_with_original_json(include_str!("fixtures/storekit_transaction.json"));
+        let Some(PlatformData::AppStore(info)) = purchase
+            .platform_data()
+            .expect("Failed to parse StoreKit transaction")
+        else {
+            panic!("Expected StoreKit transaction");
+        };
+        assert_eq!(info.transaction_id, "2000000456789012");
+        assert_eq!(
+            info.web_order_line_item_id.as_deref(),
+            Some("2000000045678901")
+        );
+        assert_eq!(info.product_type, "Auto-Renewable Subscription");
+        assert_eq!(info.in_app_ownership_type, "PURCHASED");
+        assert_eq!(info.environment, "Sandbox");
+        assert_eq!(info.expires_date, Some(1702592000000));
+        assert_eq!(info.price, Some(9990));
+        assert_eq!(info.revocation_date, None);
+    }
+
+    #[test]
+    fn test_platform_data_microsoft_store() {
+        let purchase = purchase_with_original_json(include_str!("fixtures/windows_receipt.json"));
+        let Some(PlatformData::MicrosoftStore(receipt)) = purchase
+            .platform_data()
+            .expect("Failed to parse Windows receipt")
+        else {
+            panic!("Expected Windows receipt");
+        };
+        assert_eq!(receipt.schema_version, WindowsReceipt::SCHEMA_VERSION);
+        assert_eq!(receipt.status, Some(WindowsPurchaseStatus::Succeeded));
+        assert_eq!(
+            receipt.collection.and_then(|c| c.campaign_id).as_deref(),
+            Some("spring_sale")
+        );
+    }
+
+    #[test]
+    fn test_platform_data_missing_or_unknown() {
+        assert_eq!(
+            purchase_with_original_json("")
+                .platform_data()
+                .expect("Failed to parse empty original_json"),
+            None
+        );
+        assert!(purchase_with_original_json("{}").platform_data().is_err());
+        assert!(purchase_with_original_json("not json")
+            .platform_data()
+            .is_err());
+    }
+
+    #[test]
+    fn test_platform_data_serializes_store_tag() {
+        let data = PlatformData::MicrosoftStore(WindowsReceipt::new("premium"));
+        let json = serde_json::to_string(&data).expect("Failed to serialize PlatformData");
+        assert!(json.starts_with(r#"{"store":"microsoftStore","schemaVersion":1"#));
+    }
 }
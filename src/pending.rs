@@ -0,0 +1,284 @@
+//! Registry of purchases awaiting approval or payment.
+//!
+//! A purchase is pending while it waits for Ask to Buy approval or for a
+//! deferred payment method such as cash at a store. The store later reports
+//! it through its update stream as purchased; the registry matches those
+//! updates to the pending purchase so the backend can emit
+//! `pendingPurchaseCompleted` or `pendingPurchaseDeclined`.
+//!
+//! StoreKit creates no transaction for a pending purchase and sends none when
+//! Ask to Buy is declined, so entries without a purchase token are matched by
+//! product and account token, and are declined once the request expires
+//! instead of by an update. A canceled update only declines an entry tracked
+//! by its purchase token; for other entries it is a revocation of an older
+//! transaction.
+//!
+//! Only macOS reports store updates through Rust. The iOS and Android plugins
+//! receive updates natively and keep their own registries, which follow the
+//! same rules.
+
+use std::sync::Mutex;
+
+use crate::models::*;
+
+/// How long a purchase without a transaction stays pending. Ask to Buy
+/// requests expire after 24 hours.
+const PENDING_REQUEST_TIMEOUT_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// Event emitted when the store resolves a pending purchase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PendingResolution {
+    Completed,
+    Declined,
+}
+
+impl PendingResolution {
+    pub(crate) fn event(self) -> &'static str {
+        match self {
+            Self::Completed => "pendingPurchaseCompleted",
+            Self::Declined => "pendingPurchaseDeclined",
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct PendingPurchases {
+    entries: Mutex<Vec<PendingPurchase>>,
+}
+
+impl PendingPurchases {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracks a purchase the store reported as pending, replacing an earlier
+    /// entry for the same product and account.
+    pub(crate) fn track(&self, pending: PendingPurchase) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|entry| {
+                entry.product_id != pending.product_id
+                    || entry.account_token != pending.account_token
+            });
+            entries.push(pending);
+        }
+    }
+
+    /// Pending purchases in the order they were made.
+    pub(crate) fn list(&self) -> Vec<PendingPurchase> {
+        self.entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+
+    /// Matches a purchase update against the registry, removing the pending
+    /// purchase it resolves.
+    ///
+    /// Updates that are still pending, or that match no pending purchase,
+    /// resolve nothing.
+    pub(crate) fn resolve(
+        &self,
+        update: &Purchase,
+    ) -> Option<(PendingResolution, PendingPurchase)> {
+        let resolution = match update.purchase_state {
+            PurchaseStateValue::Purchased => PendingResolution::Completed,
+            PurchaseStateValue::Canceled => PendingResolution::Declined,
+            PurchaseStateValue::Pending => return None,
+        };
+
+        let mut entries = self.entries.lock().ok()?;
+        let index = entries.iter().position(|entry| {
+            entry.matches(update)
+                && (resolution == PendingResolution::Completed || entry.purchase_token.is_some())
+        })?;
+        Some((resolution, entries.remove(index)))
+    }
+
+    /// Removes purchases without a transaction that have been pending longer
+    /// than a request stays open, which the store declined without an update.
+    pub(crate) fn expire(&self, now: i64) -> Vec<PendingPurchase> {
+        let Ok(mut entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        let (expired, pending) = entries.drain(..).partition(|entry: &PendingPurchase| {
+            entry.purchase_token.is_none()
+                && now - entry.pending_since > PENDING_REQUEST_TIMEOUT_MILLIS
+        });
+        *entries = pending;
+        expired
+    }
+}
+
+impl PendingPurchase {
+    fn matches(&self, update: &Purchase) -> bool {
+        if let Some(token) = &self.purchase_token {
+            return *token == update.purchase_token;
+        }
+        self.product_id == update.product_id
+            && (self.account_token.is_none() || self.account_token == update.account_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(product_id: &str, account_token: Option<&str>) -> PendingPurchase {
+        PendingPurchase {
+            product_id: product_id.to_string(),
+            account_token: account_token.map(str::to_string),
+            purchase_token: None,
+            pending_since: 1700000000000,
+        }
+    }
+
+    fn update(
+        product_id: &str,
+        account_token: Option<&str>,
+        purchase_state: PurchaseStateValue,
+    ) -> Purchase {
+        Purchase {
+            order_id: None,
+            package_name: "com.example.app".to_string(),
+            product_id: product_id.to_string(),
+            purchase_time: Some(1700000100000),
+            expiration_time: None,
+            is_trial: None,
+            purchase_token: "token".to_string(),
+            purchase_state,
+            is_auto_renewing: false,
+            is_acknowledged: true,
+            original_json: String::new(),
+            signature: String::new(),
+            original_id: None,
+            jws_representation: None,
+            revocation_time: None,
+            revocation_reason: None,
+            account_token: account_token.map(str::to_string),
+            ownership_type: OwnershipType::Purchased,
+            environment: StoreEnvironment::Production,
+            transaction_id: None,
+            web_order_line_item_id: None,
+            quantity: 1,
+        }
+    }
+
+    #[test]
+    fn test_resolve_completed() {
+        let registry = PendingPurchases::new();
+        registry.track(pending("premium", Some("account-1")));
+
+        let (resolution, resolved) = registry
+            .resolve(&update(
+                "premium",
+                Some("account-1"),
+                PurchaseStateValue::Purchased,
+            ))
+            .expect("Failed to resolve pending purchase");
+        assert_eq!(resolution, PendingResolution::Completed);
+        assert_eq!(resolved.product_id, "premium");
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_declined() {
+        let registry = PendingPurchases::new();
+        registry.track(PendingPurchase {
+            purchase_token: Some("token".to_string()),
+            ..pending("premium", None)
+        });
+
+        let (resolution, _) = registry
+            .resolve(&update("premium", None, PurchaseStateValue::Canceled))
+            .expect("Failed to resolve pending purchase");
+        assert_eq!(resolution, PendingResolution::Declined);
+        assert_eq!(resolution.event(), "pendingPurchaseDeclined");
+    }
+
+    #[test]
+    fn test_revocation_does_not_decline_request_without_transaction() {
+        let registry = PendingPurchases::new();
+        registry.track(pending("premium", None));
+
+        assert!(registry
+            .resolve(&update("premium", None, PurchaseStateValue::Canceled))
+            .is_none());
+        assert_eq!(registry.list().len(), 1);
+    }
+
+    #[test]
+    fn test_expire_requests_without_transaction() {
+        let registry = PendingPurchases::new();
+        registry.track(pending("premium", None));
+        registry.track(PendingPurchase {
+            purchase_token: Some("token".to_string()),
+            ..pending("coins", None)
+        });
+        let pending_since = 1700000000000;
+
+        assert!(registry
+            .expire(pending_since + PENDING_REQUEST_TIMEOUT_MILLIS)
+            .is_empty());
+
+        let expired = registry.expire(pending_since + PENDING_REQUEST_TIMEOUT_MILLIS + 1);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].product_id, "premium");
+        let entries = registry.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].product_id, "coins");
+    }
+
+    #[test]
+    fn test_resolve_keeps_still_pending_and_other_accounts() {
+        let registry = PendingPurchases::new();
+        registry.track(pending("premium", Some("account-1")));
+
+        assert!(registry
+            .resolve(&update(
+                "premium",
+                Some("account-1"),
+                PurchaseStateValue::Pending
+            ))
+            .is_none());
+        assert!(registry
+            .resolve(&update(
+                "premium",
+                Some("account-2"),
+                PurchaseStateValue::Purchased
+            ))
+            .is_none());
+        assert!(registry
+            .resolve(&update("coins", None, PurchaseStateValue::Purchased))
+            .is_none());
+        assert_eq!(registry.list().len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_by_purchase_token() {
+        let registry = PendingPurchases::new();
+        registry.track(PendingPurchase {
+            purchase_token: Some("other".to_string()),
+            ..pending("premium", None)
+        });
+
+        assert!(registry
+            .resolve(&update("premium", None, PurchaseStateValue::Purchased))
+            .is_none());
+    }
+
+    #[test]
+    fn test_track_replaces_same_product_and_account() {
+        let registry = PendingPurchases::new();
+        registry.track(pending("premium", Some("account-1")));
+        registry.track(PendingPurchase {
+            pending_since: 1700000200000,
+            ..pending("premium", Some("account-1"))
+        });
+        registry.track(pending("premium", Some("account-2")));
+
+        let entries = registry.list();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].account_token.as_deref(), Some("account-1"));
+        assert_eq!(entries[0].pending_since, 1700000200000);
+    }
+}
@@ -0,0 +1,310 @@
+//! Price comparison helpers for paywalls.
+//!
+//! Normalizes subscription prices to per-week, per-month and per-year amounts
+//! and computes savings against a reference offer, so the frontend does not
+//! need to do currency arithmetic on `priceAmountMicros` itself.
+
+use crate::models::*;
+
+/// Compare the recurring price of every subscription offer in `products`.
+///
+/// Each offer is priced by its recurring (last) pricing phase; introductory
+/// phases are ignored. Products without subscription offers are skipped.
+pub fn compare_prices(
+    products: &[Product],
+    reference_product_id: Option<&str>,
+) -> crate::Result<ComparePricesResponse> {
+    let mut comparisons = Vec::new();
+
+    for product in products {
+        for offer in product.subscription_offer_details.iter().flatten() {
+            if let Some(comparison) = compare_offer(product, offer) {
+                comparisons.push(comparison);
+            }
+        }
+    }
+
+    let reference = match reference_product_id {
+        Some(id) => Some(
+            comparisons
+                .iter()
+                .position(|c| c.product_id == id)
+                .ok_or_else(|| crate::Error::ProductNotFound(id.to_string()))?,
+        ),
+        None => comparisons
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, c)| c.price_per_month.price.micros)
+            .map(|(index, _)| index),
+    };
+
+    if let Some(index) = reference {
+        let reference_price = comparisons[index].price_per_month.price.clone();
+        for (i, comparison) in comparisons.iter_mut().enumerate() {
+            if i != index {
+                comparison.savings_percent =
+                    savings_percent(&comparison.price_per_month.price, &reference_price);
+            }
+        }
+    }
+
+    Ok(ComparePricesResponse { comparisons })
+}
+
+fn compare_offer(product: &Product, offer: &SubscriptionOffer) -> Option<PriceComparison> {
+    let phase = offer
+        .pricing_phases
+        .iter()
+        .rev()
+        .find(|phase| phase.recurrence_mode == INFINITE_RECURRING)
+        .or_else(|| offer.pricing_phases.last())?;
+    let period = phase.billing_period;
+
+    let normalize = |micros: Option<i64>| {
+        let price = Money::new(micros?, phase.price.currency.clone());
+        Some(PeriodPrice {
+            formatted_price: format_price(&price, &phase.formatted_price),
+            price,
+        })
+    };
+
+    Some(PriceComparison {
+        product_id: product.product_id.clone(),
+        base_plan_id: offer.base_plan_id.clone(),
+        offer_token: offer.offer_token.clone(),
+        offer_id: offer.offer_id.clone(),
+        billing_period: period,
+        price: phase.price.clone(),
+        formatted_price: phase.formatted_price.clone(),
+        price_per_week: normalize(period.price_per_week_micros(phase.price.micros))?,
+        price_per_month: normalize(period.price_per_month_micros(phase.price.micros))?,
+        price_per_year: normalize(period.price_per_year_micros(phase.price.micros))?,
+        savings_percent: None,
+    })
+}
+
+/// Google Play `RecurrenceMode.INFINITE_RECURRING`, also used by the other
+/// backends for the regular subscription price.
+const INFINITE_RECURRING: i32 = 1;
+
+fn savings_percent(price: &Money, reference: &Money) -> Option<u32> {
+    if price.currency != reference.currency || reference.micros <= 0 {
+        return None;
+    }
+    let saved = i128::from(reference.micros) - i128::from(price.micros);
+    let percent = (saved.max(0) * 100) / i128::from(reference.micros);
+    u32::try_from(percent).ok()
+}
+
+/// Format `price` the way `template` (a store-formatted price in the same
+/// currency) is formatted, e.g. `"1.234,56 €"` gives `"4,17 €"`.
+///
+/// Falls back to `"<amount> <currency code>"` if the template can't be parsed.
+pub fn format_price(price: &Money, template: &str) -> String {
+    let minor_units = currency_minor_units(&price.currency);
+    let layout = PriceLayout::parse(template, minor_units);
+
+    let (prefix, suffix, group, indian_grouping) = match &layout {
+        Some(layout) => (
+            layout.prefix.as_str(),
+            layout.suffix.clone(),
+            layout.group,
+            layout.indian_grouping,
+        ),
+        None => ("", format!(" {}", price.currency), None, false),
+    };
+    let decimal = layout
+        .as_ref()
+        .and_then(|l| l.decimal)
+        .unwrap_or(match group {
+            Some('.') => ',',
+            _ => '.',
+        });
+
+    // Round to the currency's minor units, half away from zero.
+    let unit = 10_u64.pow(6 - minor_units.min(6));
+    let rounded = (price.micros.unsigned_abs() + unit / 2) / unit;
+    let scale = 10_u64.pow(minor_units.min(6));
+    let (whole, fraction) = (rounded / scale, rounded % scale);
+
+    let mut formatted = String::new();
+    if price.micros < 0 {
+        formatted.push('-');
+    }
+    formatted.push_str(prefix);
+    formatted.push_str(&group_digits(&whole.to_string(), group, indian_grouping));
+    if minor_units > 0 {
+        formatted.push(decimal);
+        formatted.push_str(&format!("{fraction:0width$}", width = minor_units as usize));
+    }
+    formatted.push_str(&suffix);
+    formatted
+}
+
+fn group_digits(digits: &str, group: Option<char>, indian_grouping: bool) -> String {
+    let Some(separator) = group else {
+        return digits.to_string();
+    };
+
+    // Group sizes from the right: always 3 first, then 3s or 2s.
+    let mut sizes =
+        std::iter::once(3).chain(std::iter::repeat(if indian_grouping { 2 } else { 3 }));
+    let mut chunks = Vec::new();
+    let mut end = digits.len();
+    while end > 0 {
+        let size = sizes.next().unwrap_or(3);
+        let start = end.saturating_sub(size);
+        chunks.push(&digits[start..end]);
+        end = start;
+    }
+    chunks.reverse();
+    chunks.join(&separator.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phase(formatted: &str, micros: i64, currency: &str, period: &str) -> PricingPhase {
+        PricingPhase {
+            formatted_price: formatted.to_string(),
+            price: Money::new(micros, currency),
+            billing_period: period.parse().expect("Failed to parse billing period"),
+            billing_cycle_count: 0,
+            recurrence_mode: INFINITE_RECURRING,
+        }
+    }
+
+    fn subscription(product_id: &str, phases: Vec<PricingPhase>) -> Product {
+        Product {
+            product_id: product_id.to_string(),
+            title: product_id.to_string(),
+            description: String::new(),
+            product_type: "subs".to_string(),
+            formatted_price: None,
+            price: None,
+            subscription_offer_details: Some(vec![SubscriptionOffer {
+                offer_token: format!("{product_id}_token"),
+                base_plan_id: product_id.to_string(),
+                offer_id: None,
+                pricing_phases: phases,
+            }]),
+        }
+    }
+
+    #[test]
+    fn test_compare_prices_monthly_vs_yearly() {
+        let products = vec![
+            subscription("monthly", vec![phase("$6.99", 6_990_000, "USD", "P1M")]),
+            subscription("yearly", vec![phase("$49.99", 49_990_000, "USD", "P1Y")]),
+        ];
+
+        let response = compare_prices(&products, None).expect("Failed to compare prices");
+        let [monthly, yearly] = response.comparisons.as_slice() else {
+            panic!("Expected two comparisons");
+        };
+
+        assert_eq!(monthly.savings_percent, None);
+        assert_eq!(monthly.price_per_month.formatted_price, "$6.99");
+        assert_eq!(yearly.price_per_month.price, Money::new(4_165_833, "USD"));
+        assert_eq!(yearly.price_per_month.formatted_price, "$4.17");
+        assert_eq!(yearly.price_per_week.formatted_price, "$0.96");
+        assert_eq!(yearly.savings_percent, Some(40));
+    }
+
+    #[test]
+    fn test_compare_prices_explicit_reference() {
+        let products = vec![
+            subscription("monthly", vec![phase("$10.00", 10_000_000, "USD", "P1M")]),
+            subscription("quarterly", vec![phase("$24.00", 24_000_000, "USD", "P3M")]),
+            subscription("yearly", vec![phase("$60.00", 60_000_000, "USD", "P1Y")]),
+        ];
+
+        let response =
+            compare_prices(&products, Some("quarterly")).expect("Failed to compare prices");
+        let savings: Vec<_> = response
+            .comparisons
+            .iter()
+            .map(|c| c.savings_percent)
+            .collect();
+        assert_eq!(savings, vec![Some(0), None, Some(37)]);
+    }
+
+    #[test]
+    fn test_compare_prices_unknown_reference() {
+        let products = vec![subscription(
+            "monthly",
+            vec![phase("$6.99", 6_990_000, "USD", "P1M")],
+        )];
+        let err = compare_prices(&products, Some("missing")).expect_err("Expected an error");
+        assert!(matches!(err, crate::Error::ProductNotFound(id) if id == "missing"));
+    }
+
+    #[test]
+    fn test_compare_prices_uses_recurring_phase() {
+        let mut intro = phase("$0.99", 990_000, "USD", "P1M");
+        intro.recurrence_mode = 2;
+        let products = vec![subscription(
+            "monthly",
+            vec![intro, phase("$9.99", 9_990_000, "USD", "P1M")],
+        )];
+
+        let response = compare_prices(&products, None).expect("Failed to compare prices");
+        assert_eq!(response.comparisons[0].price, Money::new(9_990_000, "USD"));
+    }
+
+    #[test]
+    fn test_compare_prices_skips_one_time_products() {
+        let product = Product {
+            product_id: "lifetime".to_string(),
+            title: "Lifetime".to_string(),
+            description: String::new(),
+            product_type: "inapp".to_string(),
+            formatted_price: Some("$99.99".to_string()),
+            price: Some(Money::new(99_990_000, "USD")),
+            subscription_offer_details: None,
+        };
+        let response = compare_prices(&[product], None).expect("Failed to compare prices");
+        assert!(response.comparisons.is_empty());
+    }
+
+    #[test]
+    fn test_savings_percent_different_currency() {
+        let price = Money::new(1_000_000, "EUR");
+        let reference = Money::new(2_000_000, "USD");
+        assert_eq!(savings_percent(&price, &reference), None);
+    }
+
+    #[test]
+    fn test_format_price_follows_template() {
+        let cases = [
+            (4_165_833, "USD", "$49.99", "$4.17"),
+            (1_234_560_000, "USD", "$1,999.00", "$1,234.56"),
+            (1_234_560_000, "USD", "$9.99", "$1234.56"),
+            (4_165_833, "EUR", "49,99 €", "4,17 €"),
+            (1_234_560_000, "EUR", "1.999,00 €", "1.234,56 €"),
+            (
+                1_234_560_000,
+                "EUR",
+                "1\u{202F}999,00\u{00A0}€",
+                "1\u{202F}234,56\u{00A0}€",
+            ),
+            (1_234_500_000, "CHF", "CHF 1'999.00", "CHF 1'234.50"),
+            (416_666_666, "JPY", "¥5,000", "¥417"),
+            (123_456_780_000, "INR", "₹1,23,456.00", "₹1,23,456.78"),
+            (1_500_000, "KWD", "KWD 1.234", "KWD 1.500"),
+        ];
+        for (micros, currency, template, expected) in cases {
+            assert_eq!(
+                format_price(&Money::new(micros, currency), template),
+                expected,
+                "template: {template:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_price_without_template() {
+        assert_eq!(format_price(&Money::new(4_165_833, "USD"), ""), "4.17 USD");
+    }
+}
@@ -0,0 +1,60 @@
+//! Decoding of store server notifications, for backends written in Rust.
+//!
+//! Enabled by the `server` feature. Notifications are mapped to the models the
+//! plugin reports to apps, e.g. [`Purchase`](crate::Purchase) and
+//! [`IapEvent`](crate::IapEvent), so a backend and its clients share one
+//! vocabulary.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::StoreEnvironment;
+
+mod app_store;
+mod google_play;
+pub mod jws;
+
+pub use app_store::*;
+pub use google_play::*;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("malformed JWS: {0}")]
+    MalformedJws(String),
+    #[error("unsupported JWS algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("invalid certificate chain: {0}")]
+    InvalidCertificateChain(String),
+    #[error("certificate chain does not end in a trusted root")]
+    UntrustedRoot,
+    #[error("invalid JWS signature")]
+    InvalidSignature,
+    #[error("notification is for bundle id {actual}, expected {expected}")]
+    BundleIdMismatch { expected: String, actual: String },
+    #[error("notification is from environment {actual}, expected {expected:?}")]
+    EnvironmentMismatch {
+        expected: StoreEnvironment,
+        actual: String,
+    },
+    #[error("malformed Pub/Sub message: {0}")]
+    MalformedMessage(String),
+    #[error("invalid payload: {0}")]
+    InvalidPayload(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// State of an auto-renewable subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionStatus {
+    Active,
+    Expired,
+    /// Renewal failed and the store keeps retrying; the user has no access.
+    BillingRetry,
+    /// Renewal failed but the user keeps access while the store retries.
+    GracePeriod,
+    /// Paused by the user until a scheduled date. Google Play only.
+    Paused,
+    /// Refunded or revoked.
+    Revoked,
+}
@@ -0,0 +1,537 @@
+//! App Store Server Notifications V2.
+//!
+//! Apple posts `{"signedPayload": "..."}` to the URL configured in App Store
+//! Connect; see
+//! <https://developer.apple.com/documentation/appstoreservernotifications>.
+
+use serde::{Deserialize, Serialize};
+
+use super::jws::JwsVerifier;
+use super::{Error, Result, SubscriptionStatus};
+use crate::models::{
+    OwnershipType, Purchase, PurchaseStateValue, RevocationReason, StoreEnvironment,
+    StoreKitTransactionInfo,
+};
+
+const AUTO_RENEWABLE_SUBSCRIPTION: &str = "Auto-Renewable Subscription";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AppStoreNotificationType {
+    ConsumptionRequest,
+    DidChangeRenewalPref,
+    DidChangeRenewalStatus,
+    DidFailToRenew,
+    DidRenew,
+    Expired,
+    ExternalPurchaseToken,
+    GracePeriodExpired,
+    OfferRedeemed,
+    OneTimeCharge,
+    PriceIncrease,
+    Refund,
+    RefundDeclined,
+    RefundReversed,
+    RenewalExtended,
+    RenewalExtension,
+    Revoke,
+    Subscribed,
+    Test,
+    /// A type added after this version of the plugin.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AppStoreNotificationSubtype {
+    Accepted,
+    AutoRenewDisabled,
+    AutoRenewEnabled,
+    BillingRecovery,
+    BillingRetry,
+    Downgrade,
+    Failure,
+    GracePeriod,
+    InitialBuy,
+    Pending,
+    PriceIncrease,
+    ProductNotForSale,
+    Resubscribe,
+    Summary,
+    Upgrade,
+    Unreported,
+    Voluntary,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStoreNotificationData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_apple_id: Option<i64>,
+    pub bundle_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_version: Option<String>,
+    /// `"Production"` or `"Sandbox"`.
+    pub environment: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_transaction_info: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_renewal_info: Option<String>,
+    /// `1` active, `2` expired, `3` billing retry, `4` grace period,
+    /// `5` revoked. Only set for auto-renewable subscriptions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<i32>,
+}
+
+/// Decoded `signedRenewalInfo` of an auto-renewable subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStoreRenewalInfo {
+    pub original_transaction_id: String,
+    pub product_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_renew_product_id: Option<String>,
+    /// `1` if the subscription renews automatically, `0` if the user turned
+    /// renewal off.
+    pub auto_renew_status: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renewal_date: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_intent: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_period_expires_date: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_in_billing_retry_period: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_type: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_identifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_increase_status: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent_subscription_start_date: Option<i64>,
+    /// Renewal price in milliunits of `currency`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renewal_price: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_account_token: Option<String>,
+    pub environment: String,
+    pub signed_date: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestBody {
+    signed_payload: String,
+}
+
+/// A verified App Store Server Notification V2.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStoreNotification {
+    pub notification_type: AppStoreNotificationType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<AppStoreNotificationSubtype>,
+    #[serde(rename = "notificationUUID")]
+    pub notification_uuid: String,
+    pub version: String,
+    pub signed_date: i64,
+    /// Missing for notifications about several subscribers, e.g. the summary
+    /// of a renewal extension.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<AppStoreNotificationData>,
+    /// Decoded `data.signedTransactionInfo`.
+    #[serde(skip)]
+    pub transaction: Option<StoreKitTransactionInfo>,
+    /// Decoded `data.signedRenewalInfo`.
+    #[serde(skip)]
+    pub renewal_info: Option<AppStoreRenewalInfo>,
+}
+
+impl AppStoreNotification {
+    /// Verifies `signed_payload` and the signed values nested in it, and
+    /// checks that they are about the app with `bundle_id`. With
+    /// `environment`, notifications from another environment are rejected
+    /// too, e.g. sandbox notifications sent to a production endpoint.
+    ///
+    /// Notifications without `data`, e.g. summaries, carry no transaction and
+    /// are not checked.
+    pub fn decode(
+        signed_payload: &str,
+        verifier: &JwsVerifier,
+        bundle_id: &str,
+        environment: Option<StoreEnvironment>,
+    ) -> Result<Self> {
+        let mut notification: Self = verifier.decode(signed_payload)?;
+        if let Some(data) = &notification.data {
+            check_app(&data.bundle_id, &data.environment, bundle_id, environment)?;
+            notification.transaction = data
+                .signed_transaction_info
+                .as_deref()
+                .map(|jws| verifier.decode(jws))
+                .transpose()?;
+            notification.renewal_info = data
+                .signed_renewal_info
+                .as_deref()
+                .map(|jws| verifier.decode(jws))
+                .transpose()?;
+        }
+        if let Some(transaction) = &notification.transaction {
+            check_app(
+                &transaction.bundle_id,
+                &transaction.environment,
+                bundle_id,
+                environment,
+            )?;
+        }
+        Ok(notification)
+    }
+
+    /// Decodes the body of the request Apple sent, see [`Self::decode`].
+    pub fn from_request_body(
+        body: &[u8],
+        verifier: &JwsVerifier,
+        bundle_id: &str,
+        environment: Option<StoreEnvironment>,
+    ) -> Result<Self> {
+        let body: RequestBody = serde_json::from_slice(body)?;
+        Self::decode(&body.signed_payload, verifier, bundle_id, environment)
+    }
+
+    /// The transaction the notification is about, as the plugin reports it to
+    /// apps.
+    pub fn purchase(&self) -> Option<Purchase> {
+        let transaction = self.transaction.as_ref()?;
+        let signed_transaction_info = self
+            .data
+            .as_ref()
+            .and_then(|data| data.signed_transaction_info.as_deref());
+        Some(transaction.to_purchase(signed_transaction_info, self.renewal_info.as_ref()))
+    }
+
+    /// State of the subscription after this notification, `None` if it is not
+    /// about an auto-renewable subscription.
+    pub fn subscription_status(&self) -> Option<SubscriptionStatus> {
+        let is_subscription = self
+            .transaction
+            .as_ref()
+            .is_some_and(|transaction| transaction.product_type == AUTO_RENEWABLE_SUBSCRIPTION);
+        if !is_subscription {
+            return None;
+        }
+
+        match self.data.as_ref().and_then(|data| data.status) {
+            Some(1) => return Some(SubscriptionStatus::Active),
+            Some(2) => return Some(SubscriptionStatus::Expired),
+            Some(3) => return Some(SubscriptionStatus::BillingRetry),
+            Some(4) => return Some(SubscriptionStatus::GracePeriod),
+            Some(5) => return Some(SubscriptionStatus::Revoked),
+            _ => {}
+        }
+
+        // Older notifications omit `status`, fall back to their type
+        match self.notification_type {
+            AppStoreNotificationType::Subscribed
+            | AppStoreNotificationType::DidRenew
+            | AppStoreNotificationType::OfferRedeemed
+            | AppStoreNotificationType::RefundReversed
+            | AppStoreNotificationType::RenewalExtended => Some(SubscriptionStatus::Active),
+            AppStoreNotificationType::Expired | AppStoreNotificationType::GracePeriodExpired => {
+                Some(SubscriptionStatus::Expired)
+            }
+            AppStoreNotificationType::DidFailToRenew => {
+                if self.subtype == Some(AppStoreNotificationSubtype::GracePeriod) {
+                    Some(SubscriptionStatus::GracePeriod)
+                } else {
+                    Some(SubscriptionStatus::BillingRetry)
+                }
+            }
+            AppStoreNotificationType::Refund | AppStoreNotificationType::Revoke => {
+                Some(SubscriptionStatus::Revoked)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl StoreKitTransactionInfo {
+    /// Maps the transaction to a [`Purchase`] as the plugin reports it on iOS
+    /// and macOS.
+    pub fn to_purchase(
+        &self,
+        signed_transaction_info: Option<&str>,
+        renewal_info: Option<&AppStoreRenewalInfo>,
+    ) -> Purchase {
+        let revoked = self.revocation_date.is_some();
+        Purchase {
+            order_id: Some(self.transaction_id.clone()),
+            package_name: self.bundle_id.clone(),
+            product_id: self.product_id.clone(),
+            purchase_time: Some(self.purchase_date),
+            expiration_time: self.expires_date,
+            is_trial: None,
+            purchase_token: self.transaction_id.clone(),
+            purchase_state: if revoked {
+                PurchaseStateValue::Canceled
+            } else {
+                PurchaseStateValue::Purchased
+            },
+            is_auto_renewing: renewal_info.is_some_and(|info| info.auto_renew_status == 1),
+            is_acknowledged: true,
+            original_json: serde_json::to_string(self).unwrap_or_default(),
+            signature: String::new(),
+            original_id: Some(self.original_transaction_id.clone()),
+            jws_representation: signed_transaction_info.map(str::to_string),
+            revocation_time: self.revocation_date,
+            revocation_reason: self.revocation_reason.map(|reason| match reason {
+                1 => RevocationReason::DeveloperIssue,
+                _ => RevocationReason::Other,
+            }),
+            account_token: self.app_account_token.clone(),
+            ownership_type: match self.in_app_ownership_type.as_str() {
+                "FAMILY_SHARED" => OwnershipType::FamilyShared,
+                _ => OwnershipType::Purchased,
+            },
+            environment: store_environment(&self.environment),
+            transaction_id: Some(self.transaction_id.clone()),
+            web_order_line_item_id: self.web_order_line_item_id.clone(),
+            quantity: self.quantity,
+        }
+    }
+}
+
+/// Maps an App Store `environment` value.
+fn store_environment(environment: &str) -> StoreEnvironment {
+    match environment {
+        "Sandbox" => StoreEnvironment::Sandbox,
+        "Xcode" => StoreEnvironment::Xcode,
+        _ => StoreEnvironment::Production,
+    }
+}
+
+fn check_app(
+    actual_bundle_id: &str,
+    actual_environment: &str,
+    bundle_id: &str,
+    environment: Option<StoreEnvironment>,
+) -> Result<()> {
+    if actual_bundle_id != bundle_id {
+        return Err(Error::BundleIdMismatch {
+            expected: bundle_id.to_string(),
+            actual: actual_bundle_id.to_string(),
+        });
+    }
+    match environment {
+        Some(expected) if store_environment(actual_environment) != expected => {
+            Err(Error::EnvironmentMismatch {
+                expected,
+                actual: actual_environment.to_string(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::jws::testing::{sign, verifier};
+    use super::*;
+
+    const BUNDLE_ID: &str = "com.example.app";
+
+    fn transaction() -> serde_json::Value {
+        serde_json::from_str(include_str!("../fixtures/storekit_transaction.json"))
+            .expect("Failed to parse transaction fixture")
+    }
+
+    fn renewal_info() -> serde_json::Value {
+        serde_json::json!({
+            "originalTransactionId": "2000000456789012",
+            "productId": "premium_monthly",
+            "autoRenewProductId": "premium_monthly",
+            "autoRenewStatus": 1,
+            "renewalDate": 1702592000000i64,
+            "environment": "Sandbox",
+            "signedDate": 1700000003512i64
+        })
+    }
+
+    fn notification(
+        notification_type: &str,
+        transaction: &serde_json::Value,
+        status: Option<i32>,
+    ) -> String {
+        let mut data = serde_json::json!({
+            "appAppleId": 1234567890,
+            "bundleId": "com.example.app",
+            "bundleVersion": "42",
+            "environment": "Sandbox",
+            "signedTransactionInfo": sign(transaction),
+            "signedRenewalInfo": sign(&renewal_info()),
+        });
+        if let Some(status) = status {
+            data["status"] = status.into();
+        }
+        sign(&serde_json::json!({
+            "notificationType": notification_type,
+            "notificationUUID": "002e14d5-51f5-4503-b5a8-c3a1af68eb20",
+            "data": data,
+            "version": "2.0",
+            "signedDate": 1700000004000i64
+        }))
+    }
+
+    #[test]
+    fn test_decode_renewal() {
+        let signed_payload = notification("DID_RENEW", &transaction(), Some(1));
+        let notification = AppStoreNotification::decode(
+            &signed_payload,
+            &verifier(),
+            BUNDLE_ID,
+            Some(StoreEnvironment::Sandbox),
+        )
+        .expect("Failed to decode");
+
+        assert_eq!(
+            notification.notification_type,
+            AppStoreNotificationType::DidRenew
+        );
+        assert_eq!(notification.subtype, None);
+        assert_eq!(
+            notification.subscription_status(),
+            Some(SubscriptionStatus::Active)
+        );
+        assert_eq!(
+            notification
+                .renewal_info
+                .as_ref()
+                .map(|info| info.auto_renew_status),
+            Some(1)
+        );
+
+        let purchase = notification.purchase().expect("Missing purchase");
+        assert_eq!(purchase.product_id, "premium_monthly");
+        assert_eq!(purchase.package_name, "com.example.app");
+        assert_eq!(purchase.transaction_id.as_deref(), Some("2000000456789012"));
+        assert_eq!(purchase.expiration_time, Some(1702592000000));
+        assert_eq!(purchase.purchase_state, PurchaseStateValue::Purchased);
+        assert_eq!(purchase.environment, StoreEnvironment::Sandbox);
+        assert!(purchase.is_auto_renewing);
+        assert_eq!(
+            purchase.jws_representation,
+            notification
+                .data
+                .as_ref()
+                .and_then(|data| data.signed_transaction_info.clone())
+        );
+        assert!(matches!(
+            purchase.platform_data(),
+            Ok(Some(crate::models::PlatformData::AppStore(_)))
+        ));
+    }
+
+    #[test]
+    fn test_decode_refund() {
+        let mut transaction = transaction();
+        transaction["revocationDate"] = 1700500000000i64.into();
+        transaction["revocationReason"] = 1.into();
+        transaction["inAppOwnershipType"] = "FAMILY_SHARED".into();
+        let signed_payload = notification("REFUND", &transaction, None);
+        let notification =
+            AppStoreNotification::decode(&signed_payload, &verifier(), BUNDLE_ID, None)
+                .expect("Failed to decode");
+
+        assert_eq!(
+            notification.subscription_status(),
+            Some(SubscriptionStatus::Revoked)
+        );
+        let purchase = notification.purchase().expect("Missing purchase");
+        assert_eq!(purchase.purchase_state, PurchaseStateValue::Canceled);
+        assert_eq!(purchase.revocation_time, Some(1700500000000));
+        assert_eq!(
+            purchase.revocation_reason,
+            Some(RevocationReason::DeveloperIssue)
+        );
+        assert_eq!(purchase.ownership_type, OwnershipType::FamilyShared);
+    }
+
+    #[test]
+    fn test_from_request_body_test_notification() {
+        let signed_payload = sign(&serde_json::json!({
+            "notificationType": "TEST",
+            "notificationUUID": "9ad56bd2-0bc6-42e0-af24-fd996d87a1e6",
+            "data": { "bundleId": "com.example.app", "environment": "Sandbox" },
+            "version": "2.0",
+            "signedDate": 1700000004000i64
+        }));
+        let body = serde_json::json!({ "signedPayload": signed_payload }).to_string();
+        let notification =
+            AppStoreNotification::from_request_body(body.as_bytes(), &verifier(), BUNDLE_ID, None)
+                .expect("Failed to decode");
+
+        assert_eq!(
+            notification.notification_type,
+            AppStoreNotificationType::Test
+        );
+        assert!(notification.purchase().is_none());
+        assert_eq!(notification.subscription_status(), None);
+    }
+
+    #[test]
+    fn test_decode_rejects_unsigned_transaction() {
+        let signed_payload = sign(&serde_json::json!({
+            "notificationType": "ONE_TIME_CHARGE",
+            "notificationUUID": "6e2c7b1a-3f4d-4c5e-9a8b-7c6d5e4f3a2b",
+            "data": {
+                "bundleId": "com.example.app",
+                "environment": "Sandbox",
+                "signedTransactionInfo": "eyJhbGciOiJub25lIn0.e30."
+            },
+            "version": "2.0",
+            "signedDate": 1700000004000i64
+        }));
+        assert!(matches!(
+            AppStoreNotification::decode(&signed_payload, &verifier(), BUNDLE_ID, None),
+            Err(Error::UnsupportedAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_other_app() {
+        let signed_payload = notification("DID_RENEW", &transaction(), Some(1));
+        assert!(matches!(
+            AppStoreNotification::decode(&signed_payload, &verifier(), "com.example.other", None),
+            Err(Error::BundleIdMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_other_environment() {
+        let signed_payload = notification("DID_RENEW", &transaction(), Some(1));
+        assert!(matches!(
+            AppStoreNotification::decode(
+                &signed_payload,
+                &verifier(),
+                BUNDLE_ID,
+                Some(StoreEnvironment::Production)
+            ),
+            Err(Error::EnvironmentMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_transaction_for_other_app() {
+        let mut transaction = transaction();
+        transaction["bundleId"] = "com.example.other".into();
+        let signed_payload = notification("DID_RENEW", &transaction, Some(1));
+        assert!(matches!(
+            AppStoreNotification::decode(&signed_payload, &verifier(), BUNDLE_ID, None),
+            Err(Error::BundleIdMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,543 @@
+//! Google Play Real-Time Developer Notifications.
+//!
+//! Play publishes notifications to the Cloud Pub/Sub topic configured in the
+//! Play Console; see
+//! <https://developer.android.com/google/play/billing/rtdn-reference>. They
+//! are not signed: authenticate push deliveries, e.g. with the OIDC token
+//! Pub/Sub attaches, before trusting them. Notifications only name the
+//! purchase, so fetch its details from the Google Play Developer API before
+//! granting anything.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{Error, Result, SubscriptionStatus};
+use crate::models::{
+    IapEvent, OwnershipType, PendingPurchase, Purchase, PurchaseStateValue, RevocationReason,
+    StoreEnvironment,
+};
+
+/// Implements conversions between a notification type and Play's code for it.
+macro_rules! notification_type_codes {
+    ($name:ident { $($variant:ident = $code:literal,)* }) => {
+        impl From<i32> for $name {
+            fn from(code: i32) -> Self {
+                match code {
+                    $($code => Self::$variant,)*
+                    other => Self::Unknown(other),
+                }
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(notification_type: $name) -> Self {
+                match notification_type {
+                    $($name::$variant => $code,)*
+                    $name::Unknown(code) => code,
+                }
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum PlaySubscriptionNotificationType {
+    /// Recovered from account hold.
+    Recovered,
+    Renewed,
+    /// Cancelled by the user or the system; access lasts until expiry.
+    Canceled,
+    Purchased,
+    /// Renewal failed and the user has lost access.
+    OnHold,
+    /// Renewal failed but the user keeps access.
+    InGracePeriod,
+    /// Resubscribed after cancelling, before expiry.
+    Restarted,
+    PriceChangeConfirmed,
+    /// Renewal time extended.
+    Deferred,
+    Paused,
+    PauseScheduleChanged,
+    /// Revoked before expiry, e.g. refunded.
+    Revoked,
+    Expired,
+    PriceChangeUpdated,
+    /// A pending subscription purchase was cancelled.
+    PendingPurchaseCanceled,
+    /// A type added after this version of the plugin.
+    Unknown(i32),
+}
+
+notification_type_codes!(PlaySubscriptionNotificationType {
+    Recovered = 1,
+    Renewed = 2,
+    Canceled = 3,
+    Purchased = 4,
+    OnHold = 5,
+    InGracePeriod = 6,
+    Restarted = 7,
+    PriceChangeConfirmed = 8,
+    Deferred = 9,
+    Paused = 10,
+    PauseScheduleChanged = 11,
+    Revoked = 12,
+    Expired = 13,
+    PriceChangeUpdated = 19,
+    PendingPurchaseCanceled = 20,
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum PlayOneTimeProductNotificationType {
+    Purchased,
+    /// A pending purchase was cancelled.
+    Canceled,
+    /// A type added after this version of the plugin.
+    Unknown(i32),
+}
+
+notification_type_codes!(PlayOneTimeProductNotificationType {
+    Purchased = 1,
+    Canceled = 2,
+});
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaySubscriptionNotification {
+    pub version: String,
+    pub notification_type: PlaySubscriptionNotificationType,
+    pub purchase_token: String,
+    /// Deprecated by Play; absent for newer notifications.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayOneTimeProductNotification {
+    pub version: String,
+    pub notification_type: PlayOneTimeProductNotificationType,
+    pub purchase_token: String,
+    pub sku: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayVoidedPurchaseNotification {
+    pub purchase_token: String,
+    pub order_id: String,
+    /// `1` for subscriptions, `2` for one-time products.
+    pub product_type: i32,
+    /// `1` for a full refund, `2` for a refund of some units.
+    pub refund_type: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayTestNotification {
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PushBody {
+    message: PubSubMessage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PubSubMessage {
+    data: String,
+}
+
+/// A Real-Time Developer Notification, decoded from a Pub/Sub message.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayNotification {
+    pub version: String,
+    pub package_name: String,
+    /// Unix milliseconds.
+    #[serde(deserialize_with = "deserialize_millis")]
+    pub event_time_millis: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_notification: Option<PlaySubscriptionNotification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one_time_product_notification: Option<PlayOneTimeProductNotification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voided_purchase_notification: Option<PlayVoidedPurchaseNotification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_notification: Option<PlayTestNotification>,
+}
+
+impl PlayNotification {
+    /// Decodes the base64 `data` of a Pub/Sub message.
+    pub fn from_data(data: &str) -> Result<Self> {
+        let json = STANDARD
+            .decode(data)
+            .map_err(|e| Error::MalformedMessage(e.to_string()))?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Decodes the body of a Pub/Sub push request.
+    pub fn from_push_body(body: &[u8]) -> Result<Self> {
+        let body: PushBody = serde_json::from_slice(body)?;
+        Self::from_data(&body.message.data)
+    }
+
+    /// The purchase the notification is about, as the plugin reports it to
+    /// apps. Only the product, token and state are known; `product_id` is
+    /// empty for voided purchases and subscriptions Play no longer names.
+    ///
+    /// Subscriptions on hold or paused are reported as `Canceled` while they
+    /// still renew, as the user has no access until they recover or resume;
+    /// [`Self::subscription_status`] tells these states apart.
+    pub fn purchase(&self) -> Option<Purchase> {
+        if let Some(notification) = &self.subscription_notification {
+            use PlaySubscriptionNotificationType as Type;
+            let (purchase_state, is_auto_renewing) = match notification.notification_type {
+                Type::Canceled => (PurchaseStateValue::Purchased, false),
+                Type::OnHold | Type::Paused => (PurchaseStateValue::Canceled, true),
+                Type::Revoked | Type::Expired | Type::PendingPurchaseCanceled => {
+                    (PurchaseStateValue::Canceled, false)
+                }
+                _ => (PurchaseStateValue::Purchased, true),
+            };
+            let mut purchase = self.base_purchase(
+                notification.subscription_id.clone().unwrap_or_default(),
+                &notification.purchase_token,
+                purchase_state,
+            );
+            purchase.is_auto_renewing = is_auto_renewing;
+            if notification.notification_type == Type::Revoked {
+                purchase.revocation_time = Some(self.event_time_millis);
+                purchase.revocation_reason = Some(RevocationReason::Other);
+            }
+            return Some(purchase);
+        }
+
+        if let Some(notification) = &self.one_time_product_notification {
+            let purchase_state = match notification.notification_type {
+                PlayOneTimeProductNotificationType::Canceled => PurchaseStateValue::Canceled,
+                _ => PurchaseStateValue::Purchased,
+            };
+            return Some(self.base_purchase(
+                notification.sku.clone(),
+                &notification.purchase_token,
+                purchase_state,
+            ));
+        }
+
+        let notification = self.voided_purchase_notification.as_ref()?;
+        let mut purchase = self.base_purchase(
+            String::new(),
+            &notification.purchase_token,
+            PurchaseStateValue::Canceled,
+        );
+        purchase.order_id = Some(notification.order_id.clone());
+        purchase.transaction_id = Some(notification.order_id.clone());
+        purchase.revocation_time = Some(self.event_time_millis);
+        purchase.revocation_reason = Some(RevocationReason::Other);
+        Some(purchase)
+    }
+
+    /// The event the plugin would emit to apps for this notification.
+    pub fn event(&self) -> Option<IapEvent> {
+        let purchase = self.purchase()?;
+        let pending_canceled = matches!(
+            self.subscription_notification
+                .as_ref()
+                .map(|n| n.notification_type),
+            Some(PlaySubscriptionNotificationType::PendingPurchaseCanceled)
+        ) || matches!(
+            self.one_time_product_notification
+                .as_ref()
+                .map(|n| n.notification_type),
+            Some(PlayOneTimeProductNotificationType::Canceled)
+        );
+
+        if pending_canceled {
+            Some(IapEvent::PendingPurchaseDeclined(PendingPurchase {
+                product_id: purchase.product_id,
+                account_token: None,
+                purchase_token: Some(purchase.purchase_token),
+                // Play does not report when the purchase became pending
+                pending_since: self.event_time_millis,
+            }))
+        } else if purchase.revocation_time.is_some() {
+            Some(IapEvent::PurchaseRevoked(purchase))
+        } else {
+            Some(IapEvent::PurchaseUpdated(purchase))
+        }
+    }
+
+    /// State of the subscription after this notification, `None` if it is not
+    /// about a subscription or does not change its state.
+    pub fn subscription_status(&self) -> Option<SubscriptionStatus> {
+        use PlaySubscriptionNotificationType as Type;
+        let notification = self.subscription_notification.as_ref()?;
+        match notification.notification_type {
+            Type::Purchased
+            | Type::Renewed
+            | Type::Recovered
+            | Type::Restarted
+            | Type::Canceled => Some(SubscriptionStatus::Active),
+            Type::OnHold => Some(SubscriptionStatus::BillingRetry),
+            Type::InGracePeriod => Some(SubscriptionStatus::GracePeriod),
+            Type::Paused => Some(SubscriptionStatus::Paused),
+            Type::Revoked => Some(SubscriptionStatus::Revoked),
+            Type::Expired => Some(SubscriptionStatus::Expired),
+            _ => None,
+        }
+    }
+
+    fn base_purchase(
+        &self,
+        product_id: String,
+        purchase_token: &str,
+        purchase_state: PurchaseStateValue,
+    ) -> Purchase {
+        Purchase {
+            order_id: None,
+            package_name: self.package_name.clone(),
+            product_id,
+            purchase_time: None,
+            expiration_time: None,
+            is_trial: None,
+            purchase_token: purchase_token.to_string(),
+            purchase_state,
+            is_auto_renewing: false,
+            is_acknowledged: false,
+            original_json: String::new(),
+            signature: String::new(),
+            original_id: None,
+            jws_representation: None,
+            revocation_time: None,
+            revocation_reason: None,
+            account_token: None,
+            ownership_type: OwnershipType::Purchased,
+            environment: StoreEnvironment::Production,
+            transaction_id: None,
+            web_order_line_item_id: None,
+            quantity: 1,
+        }
+    }
+}
+
+/// Play sends `eventTimeMillis` as a string.
+fn deserialize_millis<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Millis {
+        String(String),
+        Number(i64),
+    }
+
+    match Millis::deserialize(deserializer)? {
+        Millis::String(millis) => millis.parse().map_err(serde::de::Error::custom),
+        Millis::Number(millis) => Ok(millis),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(notification: serde_json::Value) -> String {
+        let mut json = serde_json::json!({
+            "version": "1.0",
+            "packageName": "com.example.app",
+            "eventTimeMillis": "1700000000000"
+        });
+        json.as_object_mut().expect("Expected an object").extend(
+            notification
+                .as_object()
+                .expect("Expected an object")
+                .clone(),
+        );
+        STANDARD.encode(json.to_string())
+    }
+
+    #[test]
+    fn test_subscription_renewed() {
+        let notification = PlayNotification::from_data(&data(serde_json::json!({
+            "subscriptionNotification": {
+                "version": "1.0",
+                "notificationType": 2,
+                "purchaseToken": "token-123",
+                "subscriptionId": "premium_monthly"
+            }
+        })))
+        .expect("Failed to decode");
+
+        assert_eq!(notification.event_time_millis, 1700000000000);
+        assert_eq!(
+            notification
+                .subscription_notification
+                .as_ref()
+                .map(|n| n.notification_type),
+            Some(PlaySubscriptionNotificationType::Renewed)
+        );
+        assert_eq!(
+            notification.subscription_status(),
+            Some(SubscriptionStatus::Active)
+        );
+
+        let Some(IapEvent::PurchaseUpdated(purchase)) = notification.event() else {
+            panic!("Expected purchaseUpdated");
+        };
+        assert_eq!(purchase.product_id, "premium_monthly");
+        assert_eq!(purchase.package_name, "com.example.app");
+        assert_eq!(purchase.purchase_token, "token-123");
+        assert_eq!(purchase.purchase_state, PurchaseStateValue::Purchased);
+        assert!(purchase.is_auto_renewing);
+    }
+
+    #[test]
+    fn test_subscription_revoked() {
+        let notification = PlayNotification::from_data(&data(serde_json::json!({
+            "subscriptionNotification": {
+                "version": "1.0",
+                "notificationType": 12,
+                "purchaseToken": "token-123"
+            }
+        })))
+        .expect("Failed to decode");
+
+        assert_eq!(
+            notification.subscription_status(),
+            Some(SubscriptionStatus::Revoked)
+        );
+        let event = notification.event().expect("Missing event");
+        assert_eq!(event.name(), "purchaseRevoked");
+        let IapEvent::PurchaseRevoked(purchase) = event else {
+            unreachable!()
+        };
+        assert_eq!(purchase.purchase_state, PurchaseStateValue::Canceled);
+        assert_eq!(purchase.revocation_time, Some(1700000000000));
+    }
+
+    #[test]
+    fn test_subscription_on_hold_and_paused_do_not_entitle() {
+        for (notification_type, status) in [
+            (5, SubscriptionStatus::BillingRetry),
+            (10, SubscriptionStatus::Paused),
+        ] {
+            let notification = PlayNotification::from_data(&data(serde_json::json!({
+                "subscriptionNotification": {
+                    "version": "1.0",
+                    "notificationType": notification_type,
+                    "purchaseToken": "token-123",
+                    "subscriptionId": "premium_monthly"
+                }
+            })))
+            .expect("Failed to decode");
+
+            assert_eq!(notification.subscription_status(), Some(status));
+            let Some(IapEvent::PurchaseUpdated(purchase)) = notification.event() else {
+                panic!("Expected purchaseUpdated");
+            };
+            assert_eq!(purchase.purchase_state, PurchaseStateValue::Canceled);
+            assert!(purchase.is_auto_renewing);
+            assert_eq!(purchase.revocation_time, None);
+        }
+    }
+
+    #[test]
+    fn test_unknown_notification_type() {
+        let notification = PlayNotification::from_data(&data(serde_json::json!({
+            "subscriptionNotification": {
+                "version": "1.0",
+                "notificationType": 99,
+                "purchaseToken": "token-123"
+            }
+        })))
+        .expect("Failed to decode");
+
+        let notification_type = notification
+            .subscription_notification
+            .as_ref()
+            .map(|n| n.notification_type);
+        assert_eq!(
+            notification_type,
+            Some(PlaySubscriptionNotificationType::Unknown(99))
+        );
+        assert_eq!(notification_type.map(i32::from), Some(99));
+        assert_eq!(notification.subscription_status(), None);
+    }
+
+    #[test]
+    fn test_one_time_product_canceled() {
+        let notification = PlayNotification::from_data(&data(serde_json::json!({
+            "oneTimeProductNotification": {
+                "version": "1.0",
+                "notificationType": 2,
+                "purchaseToken": "token-456",
+                "sku": "coins_100"
+            }
+        })))
+        .expect("Failed to decode");
+
+        assert_eq!(notification.subscription_status(), None);
+        let Some(IapEvent::PendingPurchaseDeclined(pending)) = notification.event() else {
+            panic!("Expected pendingPurchaseDeclined");
+        };
+        assert_eq!(pending.product_id, "coins_100");
+        assert_eq!(pending.purchase_token.as_deref(), Some("token-456"));
+    }
+
+    #[test]
+    fn test_voided_purchase_from_push_body() {
+        let body = serde_json::json!({
+            "message": {
+                "data": data(serde_json::json!({
+                    "voidedPurchaseNotification": {
+                        "purchaseToken": "token-789",
+                        "orderId": "GPA.1234-5678-9012-34567",
+                        "productType": 2,
+                        "refundType": 1
+                    }
+                })),
+                "messageId": "136969346945",
+                "publishTime": "2023-11-14T22:13:20.000Z"
+            },
+            "subscription": "projects/example/subscriptions/play-rtdn"
+        });
+        let notification = PlayNotification::from_push_body(body.to_string().as_bytes())
+            .expect("Failed to decode");
+
+        let Some(IapEvent::PurchaseRevoked(purchase)) = notification.event() else {
+            panic!("Expected purchaseRevoked");
+        };
+        assert_eq!(
+            purchase.order_id.as_deref(),
+            Some("GPA.1234-5678-9012-34567")
+        );
+        assert_eq!(purchase.purchase_token, "token-789");
+        assert_eq!(purchase.revocation_reason, Some(RevocationReason::Other));
+    }
+
+    #[test]
+    fn test_test_notification() {
+        let notification = PlayNotification::from_data(&data(serde_json::json!({
+            "testNotification": { "version": "1.0" }
+        })))
+        .expect("Failed to decode");
+
+        assert!(notification.test_notification.is_some());
+        assert!(notification.purchase().is_none());
+        assert!(notification.event().is_none());
+    }
+
+    #[test]
+    fn test_malformed_data() {
+        assert!(matches!(
+            PlayNotification::from_data("not base64!"),
+            Err(Error::MalformedMessage(_))
+        ));
+    }
+}
@@ -0,0 +1,328 @@
+//! Verification of JWS values signed with an `x5c` certificate chain, which
+//! the App Store uses for notifications, transactions and renewal info.
+//!
+//! The chain must end in a trusted root, e.g. Apple Root CA - G3 from
+//! <https://www.apple.com/certificateauthority/>, and carry Apple's marker
+//! extensions. Revocation is not checked.
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::ecdsa::signature::Verifier;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::{Digest, Sha256, Sha384};
+use x509_cert::der::asn1::ObjectIdentifier;
+use x509_cert::der::{Decode, Encode};
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+use x509_cert::Certificate;
+
+use super::{Error, Result};
+
+const ECDSA_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const ECDSA_WITH_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.3");
+const SECP256R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const SECP384R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+/// Marks Apple's App Store signing certificates.
+const APPLE_LEAF_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113635.100.6.11.1");
+/// Marks Apple's Worldwide Developer Relations intermediate.
+const APPLE_INTERMEDIATE_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113635.100.6.2.1");
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    #[serde(default)]
+    x5c: Vec<String>,
+}
+
+pub struct JwsVerifier {
+    trusted_roots: Vec<Vec<u8>>,
+    time: Option<u64>,
+}
+
+impl JwsVerifier {
+    /// Verifier trusting the given DER-encoded root certificates.
+    pub fn new(trusted_roots: impl IntoIterator<Item = Vec<u8>>) -> Self {
+        Self {
+            trusted_roots: trusted_roots.into_iter().collect(),
+            time: None,
+        }
+    }
+
+    /// Checks certificate validity at `unix_secs` instead of now, e.g. to
+    /// process notifications that were stored for a while.
+    pub fn with_time(mut self, unix_secs: u64) -> Self {
+        self.time = Some(unix_secs);
+        self
+    }
+
+    /// Verifies `jws` and returns its payload.
+    pub fn verify(&self, jws: &str) -> Result<Vec<u8>> {
+        let mut segments = jws.split('.');
+        let (Some(header_b64), Some(payload_b64), Some(signature_b64), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return Err(Error::MalformedJws("expected three segments".to_string()));
+        };
+
+        let header: Header = serde_json::from_slice(&decode_segment(header_b64)?)
+            .map_err(|e| Error::MalformedJws(format!("invalid header: {e}")))?;
+        if header.alg != "ES256" {
+            return Err(Error::UnsupportedAlgorithm(header.alg));
+        }
+
+        let leaf = self.verify_chain(&header.x5c)?;
+        let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key(&leaf)?)
+            .map_err(|_| Error::InvalidCertificateChain("signing key is not P-256".to_string()))?;
+        let signature = p256::ecdsa::Signature::from_slice(&decode_segment(signature_b64)?)
+            .map_err(|_| Error::InvalidSignature)?;
+        key.verify(format!("{header_b64}.{payload_b64}").as_bytes(), &signature)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        decode_segment(payload_b64)
+    }
+
+    /// Verifies `jws` and deserializes its payload.
+    pub fn decode<T: DeserializeOwned>(&self, jws: &str) -> Result<T> {
+        Ok(serde_json::from_slice(&self.verify(jws)?)?)
+    }
+
+    /// Checks the leaf, intermediate and root chain, returning the leaf's key.
+    fn verify_chain(&self, x5c: &[String]) -> Result<SubjectPublicKeyInfoOwned> {
+        let [leaf, intermediate, root] = x5c else {
+            return Err(Error::InvalidCertificateChain(format!(
+                "expected 3 certificates, got {}",
+                x5c.len()
+            )));
+        };
+        let root = STANDARD
+            .decode(root)
+            .map_err(|e| Error::InvalidCertificateChain(e.to_string()))?;
+        if !self.trusted_roots.contains(&root) {
+            return Err(Error::UntrustedRoot);
+        }
+
+        let leaf = parse_certificate(leaf)?;
+        let intermediate = parse_certificate(intermediate)?;
+        let root = Certificate::from_der(&root)
+            .map_err(|e| Error::InvalidCertificateChain(e.to_string()))?;
+
+        let now = self.time.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default()
+        });
+        for cert in [&leaf, &intermediate, &root] {
+            let validity = &cert.tbs_certificate.validity;
+            if now < validity.not_before.to_unix_duration().as_secs()
+                || now > validity.not_after.to_unix_duration().as_secs()
+            {
+                return Err(Error::InvalidCertificateChain(
+                    "certificate is not valid at this time".to_string(),
+                ));
+            }
+        }
+        if !has_extension(&leaf, APPLE_LEAF_OID)
+            || !has_extension(&intermediate, APPLE_INTERMEDIATE_OID)
+        {
+            return Err(Error::InvalidCertificateChain(
+                "missing App Store certificate extension".to_string(),
+            ));
+        }
+
+        verify_issued_by(&leaf, &intermediate)?;
+        verify_issued_by(&intermediate, &root)?;
+        Ok(leaf.tbs_certificate.subject_public_key_info)
+    }
+}
+
+fn decode_segment(segment: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|e| Error::MalformedJws(e.to_string()))
+}
+
+fn parse_certificate(base64: &str) -> Result<Certificate> {
+    let der = STANDARD
+        .decode(base64)
+        .map_err(|e| Error::InvalidCertificateChain(e.to_string()))?;
+    Certificate::from_der(&der).map_err(|e| Error::InvalidCertificateChain(e.to_string()))
+}
+
+fn has_extension(cert: &Certificate, oid: ObjectIdentifier) -> bool {
+    cert.tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .any(|extension| extension.extn_id == oid)
+}
+
+fn public_key(spki: &SubjectPublicKeyInfoOwned) -> Result<&[u8]> {
+    spki.subject_public_key
+        .as_bytes()
+        .ok_or_else(|| Error::InvalidCertificateChain("malformed public key".to_string()))
+}
+
+/// Checks that `issuer`'s key signed `cert`.
+fn verify_issued_by(cert: &Certificate, issuer: &Certificate) -> Result<()> {
+    let invalid = |reason: &str| Error::InvalidCertificateChain(reason.to_string());
+
+    let tbs = cert
+        .tbs_certificate
+        .to_der()
+        .map_err(|e| Error::InvalidCertificateChain(e.to_string()))?;
+    let digest = match cert.signature_algorithm.oid {
+        ECDSA_WITH_SHA256 => Sha256::digest(&tbs).to_vec(),
+        ECDSA_WITH_SHA384 => Sha384::digest(&tbs).to_vec(),
+        oid => {
+            return Err(Error::InvalidCertificateChain(format!(
+                "unsupported certificate signature algorithm {oid}"
+            )))
+        }
+    };
+    let signature = cert
+        .signature
+        .as_bytes()
+        .ok_or_else(|| invalid("malformed certificate signature"))?;
+
+    let spki = &issuer.tbs_certificate.subject_public_key_info;
+    let curve = spki
+        .algorithm
+        .parameters
+        .as_ref()
+        .and_then(|parameters| parameters.decode_as::<ObjectIdentifier>().ok());
+    let key = public_key(spki)?;
+    let verified = match curve {
+        Some(SECP256R1) => {
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                .map_err(|_| invalid("malformed issuer key"))?;
+            let signature = p256::ecdsa::Signature::from_der(signature)
+                .map_err(|_| invalid("malformed certificate signature"))?;
+            key.verify_prehash(&digest, &signature).is_ok()
+        }
+        Some(SECP384R1) => {
+            let key = p384::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                .map_err(|_| invalid("malformed issuer key"))?;
+            let signature = p384::ecdsa::Signature::from_der(signature)
+                .map_err(|_| invalid("malformed certificate signature"))?;
+            key.verify_prehash(&digest, &signature).is_ok()
+        }
+        _ => return Err(invalid("unsupported issuer key")),
+    };
+
+    if verified {
+        Ok(())
+    } else {
+        Err(invalid("certificate signature does not match its issuer"))
+    }
+}
+
+/// Signs JWS values with the local test chain in `src/fixtures/app_store`.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::pkcs8::DecodePrivateKey;
+
+    pub(crate) const ROOT: &[u8] = include_bytes!("../fixtures/app_store/root.der");
+    pub(crate) const OTHER_ROOT: &[u8] = include_bytes!("../fixtures/app_store/other_root.der");
+    const INTERMEDIATE: &[u8] = include_bytes!("../fixtures/app_store/intermediate.der");
+    const LEAF: &[u8] = include_bytes!("../fixtures/app_store/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("../fixtures/app_store/leaf.pk8.der");
+
+    /// 2025-01-01, within the validity of the test chain.
+    pub(crate) const NOW: u64 = 1735689600;
+
+    pub(crate) fn verifier() -> JwsVerifier {
+        JwsVerifier::new([ROOT.to_vec()]).with_time(NOW)
+    }
+
+    pub(crate) fn sign_with_header(header: &serde_json::Value, payload: &[u8]) -> String {
+        let key = p256::ecdsa::SigningKey::from_pkcs8_der(LEAF_KEY)
+            .expect("Failed to load test signing key");
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(payload)
+        );
+        let signature: p256::ecdsa::Signature = key.sign(signing_input.as_bytes());
+        format!(
+            "{signing_input}.{}",
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    pub(crate) fn sign(payload: &serde_json::Value) -> String {
+        let header = serde_json::json!({
+            "alg": "ES256",
+            "x5c": [STANDARD.encode(LEAF), STANDARD.encode(INTERMEDIATE), STANDARD.encode(ROOT)],
+        });
+        sign_with_header(&header, payload.to_string().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::*;
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let jws = sign(&serde_json::json!({ "productId": "premium" }));
+        let payload: serde_json::Value = verifier().decode(&jws).expect("Failed to verify JWS");
+        assert_eq!(payload["productId"], "premium");
+    }
+
+    #[test]
+    fn test_verify_untrusted_root() {
+        let jws = sign(&serde_json::json!({}));
+        let verifier = JwsVerifier::new([OTHER_ROOT.to_vec()]).with_time(NOW);
+        assert!(matches!(verifier.verify(&jws), Err(Error::UntrustedRoot)));
+    }
+
+    #[test]
+    fn test_verify_tampered_payload() {
+        let jws = sign(&serde_json::json!({ "price": 9990 }));
+        let mut segments: Vec<&str> = jws.split('.').collect();
+        let tampered = URL_SAFE_NO_PAD.encode(r#"{"price":0}"#);
+        segments[1] = &tampered;
+        assert!(matches!(
+            verifier().verify(&segments.join(".")),
+            Err(Error::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_verify_expired_chain() {
+        let jws = sign(&serde_json::json!({}));
+        // 2035-01-01, after the test chain expired
+        let verifier = JwsVerifier::new([ROOT.to_vec()]).with_time(2051222400);
+        assert!(matches!(
+            verifier.verify(&jws),
+            Err(Error::InvalidCertificateChain(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_other_algorithms() {
+        let header = serde_json::json!({ "alg": "none", "x5c": [] });
+        let jws = sign_with_header(&header, b"{}");
+        assert!(matches!(
+            verifier().verify(&jws),
+            Err(Error::UnsupportedAlgorithm(alg)) if alg == "none"
+        ));
+    }
+
+    #[test]
+    fn test_verify_malformed() {
+        assert!(matches!(
+            verifier().verify("not-a-jws"),
+            Err(Error::MalformedJws(_))
+        ));
+    }
+}
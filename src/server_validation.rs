@@ -0,0 +1,558 @@
+//! Validation of purchases against the app's own backend.
+//!
+//! Enabled by the `validation` feature.
+//!
+//! The client posts a [`ValidationRequest`] holding the purchase to a
+//! configurable endpoint, signs the body with HMAC-SHA256 so the server can
+//! tell it came from the app, and retries transient failures with
+//! exponential backoff. Entitlements should only be granted once
+//! [`ValidationResult::grants_entitlement`] is true.
+//!
+//! HTTP is left to an [`HttpTransport`], e.g. a thin wrapper around the app's
+//! HTTP client, so the crate does not pick one.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::models::Purchase;
+
+/// Header carrying the body signature, `sha256=<hex HMAC-SHA256>`.
+pub const SIGNATURE_HEADER: &str = "X-Iap-Signature";
+
+/// Version of the request and response schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Body posted to the validation endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationRequest {
+    pub schema_version: u32,
+    pub purchase: Purchase,
+    /// Unix milliseconds at which the request was signed, so the server can
+    /// reject replays.
+    pub timestamp: i64,
+}
+
+/// Body the validation endpoint responds with.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationResponse {
+    pub valid: bool,
+    /// Unix milliseconds at which the entitlement expires, if it does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<i64>,
+    /// Why the purchase was rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Outcome of validating a purchase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationResult {
+    Valid { expiration_time: Option<i64> },
+    Invalid { reason: Option<String> },
+}
+
+impl ValidationResult {
+    /// Whether the server confirmed the purchase.
+    pub fn grants_entitlement(&self) -> bool {
+        matches!(self, Self::Valid { .. })
+    }
+}
+
+impl From<ValidationResponse> for ValidationResult {
+    fn from(response: ValidationResponse) -> Self {
+        if response.valid {
+            Self::Valid {
+                expiration_time: response.expiration_time,
+            }
+        } else {
+            Self::Invalid {
+                reason: response.reason,
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationError {
+    #[error("failed to serialize validation request: {0}")]
+    Serialize(serde_json::Error),
+    #[error("validation request failed after {attempts} attempts: {source}")]
+    Transport {
+        attempts: u32,
+        source: TransportError,
+    },
+    #[error("validation endpoint responded with status {status} after {attempts} attempts")]
+    Status { status: u16, attempts: u32 },
+    #[error("invalid validation response: {0}")]
+    InvalidResponse(serde_json::Error),
+}
+
+/// A POST request for the transport to send.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Failure to get any response, e.g. a connection error or timeout.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{0}")]
+pub struct TransportError(pub String);
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>>;
+
+/// Sends validation requests over HTTP.
+pub trait HttpTransport: Send + Sync {
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+/// Where and how to validate purchases.
+#[derive(Debug, Clone)]
+pub struct ValidationConfig {
+    /// Validation endpoint, e.g. `https://api.example.com/iap/validate`.
+    pub url: String,
+    /// Secret shared with the server for signing request bodies.
+    pub secret: Vec<u8>,
+    /// Attempts before giving up, including the first. At least 1.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every further retry.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl ValidationConfig {
+    pub fn new(url: impl Into<String>, secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            url: url.into(),
+            secret: secret.into(),
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+
+    /// Delay before retry number `retry`, counting from 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+pub struct ValidationClient<T> {
+    transport: T,
+    config: ValidationConfig,
+}
+
+impl<T: HttpTransport> ValidationClient<T> {
+    pub fn new(transport: T, config: ValidationConfig) -> Self {
+        Self { transport, config }
+    }
+
+    /// Validates `purchase` with the server.
+    ///
+    /// Transport errors, `429` and `5xx` responses are retried; other
+    /// non-success statuses fail immediately.
+    pub async fn validate(&self, purchase: &Purchase) -> Result<ValidationResult, ValidationError> {
+        let body = serde_json::to_vec(&ValidationRequest {
+            schema_version: SCHEMA_VERSION,
+            purchase: purchase.clone(),
+            timestamp: crate::time::now_millis(),
+        })
+        .map_err(ValidationError::Serialize)?;
+        let request = HttpRequest {
+            url: self.config.url.clone(),
+            headers: vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                (
+                    SIGNATURE_HEADER.to_string(),
+                    sign(&self.config.secret, &body),
+                ),
+            ],
+            body,
+        };
+
+        let max_attempts = self.config.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let error = match self.transport.post(request.clone()).await {
+                Ok(response) if (200..300).contains(&response.status) => {
+                    let response: ValidationResponse = serde_json::from_slice(&response.body)
+                        .map_err(ValidationError::InvalidResponse)?;
+                    return Ok(response.into());
+                }
+                Ok(response) => {
+                    let error = ValidationError::Status {
+                        status: response.status,
+                        attempts: attempt,
+                    };
+                    if response.status != 429 && response.status < 500 {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(source) => ValidationError::Transport {
+                    attempts: attempt,
+                    source,
+                },
+            };
+
+            if attempt >= max_attempts {
+                return Err(error);
+            }
+            log::debug!("Retrying purchase validation: {error}");
+            tokio::time::sleep(self.config.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Signature of `body` for the [`SIGNATURE_HEADER`].
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    let mut signature = String::from("sha256=");
+    for byte in mac.finalize().into_bytes() {
+        signature.push_str(&format!("{byte:02x}"));
+    }
+    signature
+}
+
+/// Checks a [`SIGNATURE_HEADER`] value in constant time, for servers
+/// receiving validation requests.
+pub fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Some(expected) = decode_hex(hex) else {
+        return false;
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+
+    /// In-process HTTP server answering each connection with the next
+    /// scripted response and recording the requests it received.
+    struct StubServer {
+        url: String,
+        requests: Arc<Mutex<Vec<HttpRequest>>>,
+    }
+
+    impl StubServer {
+        fn start(responses: Vec<(u16, &'static str)>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+            let url = format!(
+                "http://{}/validate",
+                listener.local_addr().expect("Failed to get stub address")
+            );
+            let requests = Arc::new(Mutex::new(Vec::new()));
+
+            let recorded = requests.clone();
+            std::thread::spawn(move || {
+                for (status, body) in responses {
+                    let Ok((mut stream, _)) = listener.accept() else {
+                        return;
+                    };
+                    let request = read_request(&mut stream);
+                    recorded.lock().unwrap().push(request);
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                }
+            });
+
+            Self { url, requests }
+        }
+
+        fn requests(&self) -> Vec<HttpRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn read_request(stream: &mut TcpStream) -> HttpRequest {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let path = request_line
+            .split(' ')
+            .nth(1)
+            .unwrap_or_default()
+            .to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(": ") {
+                headers.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        let length = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+            .and_then(|(_, value)| value.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        HttpRequest {
+            url: path,
+            headers,
+            body,
+        }
+    }
+
+    /// Minimal HTTP/1.1 client for `http://` URLs.
+    struct TcpTransport;
+
+    impl HttpTransport for TcpTransport {
+        fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async move {
+                let rest = request
+                    .url
+                    .strip_prefix("http://")
+                    .ok_or_else(|| TransportError("unsupported URL".to_string()))?;
+                let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+
+                let mut stream =
+                    TcpStream::connect(host).map_err(|e| TransportError(e.to_string()))?;
+                let mut head = format!("POST /{path} HTTP/1.1\r\nHost: {host}\r\n");
+                for (name, value) in &request.headers {
+                    head.push_str(&format!("{name}: {value}\r\n"));
+                }
+                head.push_str(&format!("Content-Length: {}\r\n\r\n", request.body.len()));
+                stream
+                    .write_all(head.as_bytes())
+                    .and_then(|_| stream.write_all(&request.body))
+                    .map_err(|e| TransportError(e.to_string()))?;
+
+                let mut response = String::new();
+                stream
+                    .read_to_string(&mut response)
+                    .map_err(|e| TransportError(e.to_string()))?;
+                let status = response
+                    .split(' ')
+                    .nth(1)
+                    .and_then(|status| status.parse().ok())
+                    .ok_or_else(|| TransportError("malformed response".to_string()))?;
+                let body = response
+                    .split_once("\r\n\r\n")
+                    .map(|(_, body)| body.as_bytes().to_vec())
+                    .unwrap_or_default();
+                Ok(HttpResponse { status, body })
+            })
+        }
+    }
+
+    /// Transport whose every request fails to connect.
+    struct UnreachableTransport;
+
+    impl HttpTransport for UnreachableTransport {
+        fn post(&self, _request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async { Err(TransportError("connection refused".to_string())) })
+        }
+    }
+
+    fn purchase() -> Purchase {
+        serde_json::from_str(
+            r#"{
+                "orderId": "GPA.3372-5117-6393-28412",
+                "packageName": "com.example.app",
+                "productId": "premium_monthly",
+                "purchaseToken": "token",
+                "purchaseState": 0,
+                "isAutoRenewing": true,
+                "isAcknowledged": true,
+                "originalJson": "{}",
+                "signature": "sig",
+                "originalId": null
+            }"#,
+        )
+        .expect("Failed to deserialize Purchase")
+    }
+
+    fn config(url: &str) -> ValidationConfig {
+        ValidationConfig {
+            initial_backoff: Duration::from_millis(1),
+            ..ValidationConfig::new(url, "secret")
+        }
+    }
+
+    fn header<'a>(request: &'a HttpRequest, name: &str) -> Option<&'a str> {
+        request
+            .headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[tokio::test]
+    async fn test_validate_valid_purchase() {
+        let server = StubServer::start(vec![(
+            200,
+            r#"{"valid":true,"expirationTime":1702592000000}"#,
+        )]);
+        let client = ValidationClient::new(TcpTransport, config(&server.url));
+
+        let result = client
+            .validate(&purchase())
+            .await
+            .expect("Failed to validate purchase");
+        assert_eq!(
+            result,
+            ValidationResult::Valid {
+                expiration_time: Some(1702592000000)
+            }
+        );
+        assert!(result.grants_entitlement());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "/validate");
+        let signature = header(&requests[0], SIGNATURE_HEADER).expect("Missing signature header");
+        assert!(verify_signature(b"secret", &requests[0].body, signature));
+
+        let request: ValidationRequest =
+            serde_json::from_slice(&requests[0].body).expect("Failed to parse request body");
+        assert_eq!(request.schema_version, SCHEMA_VERSION);
+        assert_eq!(request.purchase.product_id, "premium_monthly");
+    }
+
+    #[tokio::test]
+    async fn test_validate_invalid_purchase() {
+        let server = StubServer::start(vec![(200, r#"{"valid":false,"reason":"refunded"}"#)]);
+        let client = ValidationClient::new(TcpTransport, config(&server.url));
+
+        let result = client
+            .validate(&purchase())
+            .await
+            .expect("Failed to validate purchase");
+        assert_eq!(
+            result,
+            ValidationResult::Invalid {
+                reason: Some("refunded".to_string())
+            }
+        );
+        assert!(!result.grants_entitlement());
+    }
+
+    #[tokio::test]
+    async fn test_validate_retries_server_errors() {
+        let server = StubServer::start(vec![(503, ""), (429, ""), (200, r#"{"valid":true}"#)]);
+        let client = ValidationClient::new(TcpTransport, config(&server.url));
+
+        let result = client
+            .validate(&purchase())
+            .await
+            .expect("Failed to validate purchase");
+        assert!(result.grants_entitlement());
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_validate_does_not_retry_client_errors() {
+        let server = StubServer::start(vec![(401, ""), (200, r#"{"valid":true}"#)]);
+        let client = ValidationClient::new(TcpTransport, config(&server.url));
+
+        let err = client.validate(&purchase()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError::Status {
+                status: 401,
+                attempts: 1
+            }
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_validate_gives_up_after_max_attempts() {
+        let client = ValidationClient::new(UnreachableTransport, config("http://unused"));
+
+        let err = client.validate(&purchase()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError::Transport { attempts: 3, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_validate_rejects_malformed_response() {
+        let server = StubServer::start(vec![(200, "not json")]);
+        let client = ValidationClient::new(TcpTransport, config(&server.url));
+
+        let err = client.validate(&purchase()).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidResponse(_)));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let config = ValidationConfig::new("https://example.com", "secret");
+        assert_eq!(config.backoff(1), Duration::from_millis(500));
+        assert_eq!(config.backoff(2), Duration::from_secs(1));
+        assert_eq!(config.backoff(5), Duration::from_secs(8));
+        assert_eq!(config.backoff(40), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_sign_rfc_4231() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let signature = sign(b"secret", b"body");
+        assert!(verify_signature(b"secret", b"body", &signature));
+        assert!(!verify_signature(b"other", b"body", &signature));
+        assert!(!verify_signature(b"secret", b"tampered", &signature));
+        assert!(!verify_signature(b"secret", b"body", "sha256=zz"));
+        assert!(!verify_signature(b"secret", b"body", "body"));
+    }
+}
@@ -0,0 +1,9 @@
+//! Wall-clock helpers shared by the backends and the validation client.
+
+/// Current time in Unix milliseconds.
+pub(crate) fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
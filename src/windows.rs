@@ -1,40 +1,105 @@
-use serde::de::DeserializeOwned;
-use tauri::Emitter;
 use tauri::Manager;
-use tauri::{plugin::PluginApi, AppHandle, Runtime};
-use windows::core::{Interface, HSTRING};
+use tauri::{plugin::PluginApi, AppHandle, Runtime, Window};
+use windows::core::{Interface, RuntimeType, GUID, HRESULT, HSTRING};
 use windows::{
-    Foundation::DateTime,
+    Foundation::{DateTime, IAsyncOperation, Uri},
+    Globalization::GeographicRegion,
     Services::Store::{
-        StoreContext, StoreLicense, StoreProduct, StorePurchaseProperties, StorePurchaseStatus,
+        StoreCollectionData, StoreConsumableResult, StoreConsumableStatus, StoreContext,
+        StoreDurationUnit, StoreLicense, StoreProduct, StorePurchaseProperties,
+        StorePurchaseStatus,
     },
+    System::{Launcher, UserProfile::GlobalizationPreferences},
     Win32::UI::Shell::IInitializeWithWindow,
 };
 use windows_collections::IIterable;
 
+use crate::cache::ProductCache;
 use crate::error::{ErrorResponse, PluginInvokeError};
 use crate::models::*;
-use std::sync::{Arc, RwLock};
-
-pub fn init<R: Runtime, C: DeserializeOwned>(
+use crate::Config;
+use std::collections::HashMap;
+use std::future::IntoFuture;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+const SUPPORTED_OPERATIONS: &[StoreOperation] = &[
+    StoreOperation::GetProducts,
+    StoreOperation::Purchase,
+    StoreOperation::RestorePurchases,
+    StoreOperation::AcknowledgePurchase,
+    StoreOperation::ConsumePurchase,
+    StoreOperation::GetProductStatus,
+    StoreOperation::GetStorefront,
+    StoreOperation::GetConsumableBalanceRemaining,
+    StoreOperation::GetAppLicense,
+    StoreOperation::ShowManageSubscriptions,
+    StoreOperation::GetPendingPurchases,
+];
+
+/// How often, and how many times, `purchase` queries the user's collection
+/// for a purchase that just completed.
+const COLLECTION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const COLLECTION_POLL_ATTEMPTS: u32 = 10;
+
+/// Namespace for the fulfillment tracking ids derived from purchase tokens.
+const FULFILLMENT_NAMESPACE: uuid::Uuid =
+    uuid::Uuid::from_u128(0x6f1c_2a4e_8d3b_4f57_9e62_b0a7_c5d4_1e89);
+
+pub fn init<R: Runtime>(
     app: &AppHandle<R>,
-    _api: PluginApi<R, C>,
+    api: PluginApi<R, Option<Config>>,
 ) -> crate::Result<Iap<R>> {
+    let config = api.config().clone().unwrap_or_default();
+
     Ok(Iap {
         app_handle: app.clone(),
         store_context: Arc::new(RwLock::new(None)),
+        window_label: config.window_label,
+        invoking_window_label: Mutex::new(None),
+        request_timeout: config.request_timeout(),
+        product_cache: ProductCache::new(config.product_cache_ttl()),
+        storefront: Mutex::new(None),
     })
 }
 
+/// A `StoreContext` and the label of the window its UI is attached to.
+struct WindowStoreContext {
+    window_label: String,
+    context: StoreContext,
+}
+
 /// Access to the iap APIs.
 pub struct Iap<R: Runtime> {
     app_handle: AppHandle<R>,
-    store_context: Arc<RwLock<Option<StoreContext>>>,
+    store_context: Arc<RwLock<Option<WindowStoreContext>>>,
+    /// Window label from the plugin config, takes precedence over the invoking window.
+    window_label: Option<String>,
+    /// Window that last invoked a plugin command.
+    invoking_window_label: Mutex<Option<String>>,
+    /// Timeout for Store requests that don't wait for the user.
+    request_timeout: Option<Duration>,
+    product_cache: ProductCache,
+    /// Last storefront returned by `get_storefront`, to detect changes.
+    storefront: Mutex<Option<Storefront>>,
 }
 
 impl<R: Runtime> Iap<R> {
-    /// Get or create the StoreContext instance
+    /// Attach Store UI to `window`, unless a window label is configured.
+    ///
+    /// Called with the invoking window by `purchase`, the only command that
+    /// shows a Store dialog; `show_manage_subscriptions` opens the Store app.
+    pub fn set_parent_window(&self, window: &Window<R>) {
+        if let Ok(mut label) = self.invoking_window_label.lock() {
+            *label = Some(window.label().to_string());
+        }
+    }
+
+    /// Get or create the StoreContext instance, re-initializing it when the
+    /// parent window changes
     fn get_store_context(&self) -> crate::Result<StoreContext> {
+        let window = self.parent_window()?;
+
         let mut context_guard = self.store_context.write().map_err(|e| {
             crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
                 code: Some("internalError".to_string()),
@@ -43,44 +108,77 @@ impl<R: Runtime> Iap<R> {
             }))
         })?;
 
-        if context_guard.is_none() {
-            // Get the default store context for the current user
-            let context = StoreContext::GetDefault()?;
+        if let Some(current) = context_guard.as_ref() {
+            if current.window_label == window.label() {
+                return Ok(current.context.clone());
+            }
+            log::debug!(
+                "Parent window changed from '{}' to '{}', re-initializing store context",
+                current.window_label,
+                window.label()
+            );
+        }
 
-            let window = self.app_handle.get_webview_window("main").ok_or_else(|| {
-                crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
-                    code: Some("windowError".to_string()),
-                    message: Some("Failed to get main window".to_string()),
-                    data: (),
-                }))
-            })?;
-            let hwnd = window.hwnd().map_err(|e| {
-                crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
-                    code: Some("windowError".to_string()),
-                    message: Some(format!("Failed to get window handle: {:?}", e)),
-                    data: (),
-                }))
-            })?;
+        // Get the default store context for the current user
+        let context = StoreContext::GetDefault()?;
 
-            // Cast the WinRT object to IInitializeWithWindow and initialize it with your HWND
-            let init = context.cast::<IInitializeWithWindow>()?;
-            unsafe {
-                init.Initialize(hwnd)?;
-            }
+        let hwnd = window.hwnd().map_err(|e| {
+            crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
+                code: Some("windowError".to_string()),
+                message: Some(format!("Failed to get window handle: {:?}", e)),
+                data: (),
+            }))
+        })?;
 
-            *context_guard = Some(context);
+        // Cast the WinRT object to IInitializeWithWindow and initialize it with your HWND
+        let init = context.cast::<IInitializeWithWindow>()?;
+        unsafe {
+            init.Initialize(hwnd)?;
         }
 
-        Ok(context_guard
-            .as_ref()
-            .ok_or_else(|| {
-                crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
-                    code: Some("storeNotInitialized".to_string()),
-                    message: Some("Store context not initialized".to_string()),
-                    data: (),
-                }))
-            })?
-            .clone())
+        *context_guard = Some(WindowStoreContext {
+            window_label: window.label().to_string(),
+            context: context.clone(),
+        });
+
+        Ok(context)
+    }
+
+    /// Window to attach Store UI to.
+    fn parent_window(&self) -> crate::Result<Window<R>> {
+        let windows = self.app_handle.windows();
+        let open_labels: Vec<&str> = windows.keys().map(String::as_str).collect();
+        let invoking_label = self
+            .invoking_window_label
+            .lock()
+            .ok()
+            .and_then(|label| label.clone());
+
+        select_window_label(
+            self.window_label.as_deref(),
+            invoking_label.as_deref(),
+            &open_labels,
+        )
+        .and_then(|label| windows.get(label).cloned())
+        .ok_or_else(|| {
+            let message = match &self.window_label {
+                Some(label) => format!("Failed to get window '{label}'"),
+                None => "No window to attach the Store UI to".to_string(),
+            };
+            crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
+                code: Some("windowError".to_string()),
+                message: Some(message),
+                data: (),
+            }))
+        })
+    }
+
+    /// Await a Store request, cancelling it after the configured timeout.
+    async fn request<T: RuntimeType + 'static>(
+        &self,
+        operation: windows::core::Result<IAsyncOperation<T>>,
+    ) -> crate::Result<T> {
+        await_operation(operation?, self.request_timeout).await
     }
 
     /// Convert Windows DateTime to Unix timestamp in milliseconds
@@ -96,15 +194,114 @@ impl<R: Runtime> Iap<R> {
         unix_seconds * 1000 // Convert to milliseconds
     }
 
+    /// Acquisition time of a collection entry, if the Store reports one.
+    fn acquired_millis(data: Option<&StoreCollectionData>) -> Option<i64> {
+        known_millis(Self::datetime_to_unix_millis(&data?.AcquiredDate().ok()?))
+    }
+
+    /// Purchase and expiration times of a SKU as reported by the user's
+    /// collection, falling back to the license expiration.
+    ///
+    /// Times the Store does not report are left unknown instead of estimated.
+    fn entitlement_times(
+        data: Option<&StoreCollectionData>,
+        license: Option<&StoreLicense>,
+    ) -> EntitlementTimes {
+        let millis = |date: windows::core::Result<DateTime>| {
+            date.ok()
+                .and_then(|date| known_millis(Self::datetime_to_unix_millis(&date)))
+        };
+
+        EntitlementTimes {
+            purchase_time: data
+                .and_then(|data| millis(data.AcquiredDate()).or_else(|| millis(data.StartDate()))),
+            expiration_time: data
+                .and_then(|data| millis(data.EndDate()))
+                .or_else(|| license.and_then(|license| millis(license.ExpirationDate()))),
+            is_trial: data.and_then(|data| data.IsTrial().ok()),
+        }
+    }
+
+    /// Receipt serialized into `original_json` for a SKU's license and
+    /// collection entry.
+    fn receipt(
+        product_id: &str,
+        sku_store_id: Option<&str>,
+        data: Option<&StoreCollectionData>,
+        license: Option<&StoreLicense>,
+    ) -> crate::Result<WindowsReceipt> {
+        Ok(WindowsReceipt {
+            sku_store_id: sku_store_id.map(str::to_string),
+            license: license.map(Self::license_details).transpose()?,
+            collection: data.map(Self::collection_details).transpose()?,
+            ..WindowsReceipt::new(product_id)
+        })
+    }
+
+    fn license_details(license: &StoreLicense) -> windows::core::Result<WindowsLicenseDetails> {
+        Ok(WindowsLicenseDetails {
+            is_active: license.IsActive()?,
+            expiration_date: known_millis(Self::datetime_to_unix_millis(
+                &license.ExpirationDate()?,
+            )),
+            in_app_offer_token: license.InAppOfferToken()?.to_string(),
+        })
+    }
+
+    fn collection_details(
+        data: &StoreCollectionData,
+    ) -> windows::core::Result<WindowsCollectionData> {
+        let is_trial = data.IsTrial()?;
+        let non_empty = |value: HSTRING| (!value.is_empty()).then(|| value.to_string());
+
+        Ok(WindowsCollectionData {
+            acquired_date: known_millis(Self::datetime_to_unix_millis(&data.AcquiredDate()?)),
+            start_date: known_millis(Self::datetime_to_unix_millis(&data.StartDate()?)),
+            end_date: known_millis(Self::datetime_to_unix_millis(&data.EndDate()?)),
+            is_trial,
+            // TimeSpan is in 100-nanosecond intervals
+            trial_time_remaining_secs: is_trial
+                .then(|| data.TrialTimeRemaining())
+                .transpose()?
+                .map(|remaining| remaining.Duration / 10_000_000),
+            campaign_id: non_empty(data.CampaignId()?),
+            developer_offer_id: non_empty(data.DeveloperOfferId()?),
+        })
+    }
+
     /// Emit an event to the frontend (equivalent to iOS/Android `trigger` method).
-    fn trigger<S: serde::Serialize + Clone>(&self, event: &str, payload: S) {
-        let _ = self.app_handle.emit(event, payload);
+    ///
+    /// Delivered through the plugin listeners registered by `addPluginListener`.
+    fn trigger<S: serde::Serialize>(&self, event: &str, payload: S) {
+        let result = serde_json::to_string(&payload)
+            .map_err(|e| PluginInvokeError::CannotSerializePayload(e).into())
+            .and_then(|json| crate::listeners::trigger(event, json));
+        if let Err(e) = result {
+            log::warn!("Failed to trigger event '{event}': {e}");
+        }
     }
 
     pub async fn get_products(
         &self,
         product_ids: Vec<String>,
         product_type: String,
+        refresh: bool,
+    ) -> crate::Result<GetProductsResponse> {
+        self.product_cache
+            .get_products(
+                product_ids,
+                product_type,
+                refresh,
+                |product_ids, product_type| self.fetch_products(product_ids, product_type),
+            )
+            .await
+    }
+
+    /// Query products from the Store, bypassing the product cache.
+    async fn fetch_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: String,
     ) -> crate::Result<GetProductsResponse> {
         let context = self.get_store_context()?;
 
@@ -133,9 +330,9 @@ impl<R: Runtime> Iap<R> {
         let product_kinds: IIterable<HSTRING> = product_kinds.into();
 
         // Query products from the store
-        let query_result = context
-            .GetStoreProductsAsync(&product_kinds, &store_ids)
-            .and_then(|async_op| async_op.get())?;
+        let query_result = self
+            .request(context.GetStoreProductsAsync(&product_kinds, &store_ids))
+            .await?;
 
         // Check for any errors
         let extended_error = query_result.ExtendedError()?;
@@ -167,7 +364,7 @@ impl<R: Runtime> Iap<R> {
             iterator.MoveNext()?;
         }
 
-        Ok(GetProductsResponse { products })
+        Ok(GetProductsResponse::from_requested(&product_ids, products))
     }
 
     fn convert_store_product_to_product(
@@ -187,18 +384,15 @@ impl<R: Runtime> Iap<R> {
 
         let currency_code = price.CurrencyCode()?.to_string();
 
-        // Get the raw price value
-        let formatted_base_price = price.FormattedBasePrice()?.to_string();
-
-        // Parse price to get numeric value (remove currency symbols)
-        let price_value = formatted_base_price
-            .chars()
-            .filter(|c| c.is_numeric() || *c == '.')
-            .collect::<String>()
-            .parse::<f64>()
-            .unwrap_or(0.0);
-
-        let price_amount_micros = (price_value * 1_000_000.0) as i64;
+        // Parse the amount from the same string that is displayed, so the
+        // micros always agree with `formatted_price`
+        let product_price = match Money::parse_formatted(&formatted_price, currency_code.as_str()) {
+            Ok(money) => Some(money),
+            Err(e) => {
+                log::warn!("Failed to parse price for product {product_id}: {e}");
+                None
+            }
+        };
 
         // Handle subscription offers if this is a subscription product
         let subscription_offer_details = if product_type == "subs" {
@@ -212,34 +406,42 @@ impl<R: Runtime> Iap<R> {
                 let sku = skus.GetAt(i)?;
 
                 let sku_id = sku.StoreId()?.to_string();
-                sku.StoreId()?.to_string();
 
                 let sku_price = sku.Price()?;
+                let sku_formatted_price = sku_price.FormattedPrice()?.to_string();
 
                 // Check if this SKU has subscription info
                 let subscription_info = sku.SubscriptionInfo();
 
                 if let Ok(info) = subscription_info {
-                    let billing_period = info.BillingPeriod()?;
-                    let billing_period_unit = info.BillingPeriodUnit()?;
+                    // Like the product price, a SKU the plugin cannot describe
+                    // is left out rather than failing the whole query
+                    let billing_period = match Self::convert_billing_period(
+                        info.BillingPeriod()?,
+                        info.BillingPeriodUnit()?,
+                    ) {
+                        Ok(billing_period) => billing_period,
+                        Err(e) => {
+                            log::warn!("Skipping SKU {sku_id} of product {product_id}: {e}");
+                            continue;
+                        }
+                    };
 
-                    let billing_period_str = format!(
-                        "P{}{}",
-                        billing_period,
-                        match billing_period_unit.0 {
-                            0 => "D", // Day
-                            1 => "W", // Week
-                            2 => "M", // Month
-                            3 => "Y", // Year
-                            _ => "M",
+                    let price = match Money::parse_formatted(
+                        &sku_formatted_price,
+                        sku_price.CurrencyCode()?.to_string(),
+                    ) {
+                        Ok(price) => price,
+                        Err(e) => {
+                            log::warn!("Failed to parse price for SKU {sku_id}: {e}");
+                            continue;
                         }
-                    );
+                    };
 
                     let pricing_phase = PricingPhase {
-                        formatted_price: sku_price.FormattedPrice()?.to_string(),
-                        price_currency_code: currency_code.clone(),
-                        price_amount_micros,
-                        billing_period: billing_period_str,
+                        formatted_price: sku_formatted_price,
+                        price,
+                        billing_period,
                         billing_cycle_count: 0, // Windows doesn't provide this directly
                         recurrence_mode: 1,     // Infinite recurring
                     };
@@ -270,20 +472,53 @@ impl<R: Runtime> Iap<R> {
             description,
             product_type: product_type.to_string(),
             formatted_price: Some(formatted_price),
-            price_currency_code: Some(currency_code),
-            price_amount_micros: Some(price_amount_micros),
+            price: product_price,
             subscription_offer_details,
         })
     }
 
+    /// Convert a Store subscription billing period into a [`BillingPeriod`].
+    ///
+    /// Sub-day units (minutes, hours) have no ISO-8601 date representation and
+    /// are rejected rather than silently mapped to another unit.
+    fn convert_billing_period(
+        period: u32,
+        unit: StoreDurationUnit,
+    ) -> crate::Result<BillingPeriod> {
+        let unit = match unit {
+            StoreDurationUnit::Day => PeriodUnit::Day,
+            StoreDurationUnit::Week => PeriodUnit::Week,
+            StoreDurationUnit::Month => PeriodUnit::Month,
+            StoreDurationUnit::Year => PeriodUnit::Year,
+            _ => {
+                return Err(crate::Error::PluginInvoke(
+                    PluginInvokeError::InvokeRejected(ErrorResponse {
+                        code: Some("invalidBillingPeriod".to_string()),
+                        message: Some(format!("Unsupported billing period unit: {}", unit.0)),
+                        data: (),
+                    }),
+                ))
+            }
+        };
+
+        BillingPeriod::new(period, unit).map_err(|e| {
+            crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
+                code: Some("invalidBillingPeriod".to_string()),
+                message: Some(e.to_string()),
+                data: (),
+            }))
+        })
+    }
+
     pub async fn purchase(&self, payload: PurchaseRequest) -> crate::Result<Purchase> {
         let context = self.get_store_context()?;
 
-        // Get the product first to ensure it exists
+        // Get the product first to ensure it exists (served from the cache if fresh)
         let products_response = self
             .get_products(
                 vec![payload.product_id.clone()],
                 payload.product_type.clone(),
+                false,
             )
             .await?;
 
@@ -302,6 +537,17 @@ impl<R: Runtime> Iap<R> {
 
         let store_id = HSTRING::from(&payload.product_id);
 
+        // Token of an earlier purchase of the add-on, to tell the new one apart
+        let previous_token = self
+            .user_collection(&context)
+            .await
+            .ok()
+            .and_then(|collection| {
+                collection_entry(&collection, &payload.product_id).map(|(sku_store_id, data)| {
+                    purchase_token(sku_store_id, Self::acquired_millis(Some(data)))
+                })
+            });
+
         // Create purchase properties if we have an offer token (for subscriptions)
         let offer_token = payload.options.and_then(|opts| opts.offer_token);
         let purchase_result = if let Some(token) = offer_token {
@@ -311,14 +557,15 @@ impl<R: Runtime> Iap<R> {
             properties
                 .SetExtendedJsonData(&HSTRING::from(format!(r#"{{"skuId":"{}"}}"#, token)))?;
 
-            context
-                .RequestPurchaseWithPurchasePropertiesAsync(&store_id, &properties)
-                .and_then(|async_op| async_op.get())?
+            // The purchase dialog waits for the user, so it is never timed out
+            await_operation(
+                context.RequestPurchaseWithPurchasePropertiesAsync(&store_id, &properties)?,
+                None,
+            )
+            .await?
         } else {
             // Simple purchase without properties
-            context
-                .RequestPurchaseAsync(&store_id)
-                .and_then(|async_op| async_op.get())?
+            await_operation(context.RequestPurchaseAsync(&store_id)?, None).await?
         };
 
         // Check purchase status
@@ -365,44 +612,61 @@ impl<R: Runtime> Iap<R> {
             }
         };
 
-        // Get extended error info if available
-        let extended_error = purchase_result.ExtendedError().ok();
-        let error_message = if let Some(error) = extended_error {
-            error.message()
-        } else {
-            String::new()
+        // An add-on that was already purchased keeps its earlier token
+        let previous_token = previous_token.filter(|_| status == StorePurchaseStatus::Succeeded);
+        let purchased_sku = self
+            .purchased_sku(&context, &payload.product_id, previous_token.as_deref())
+            .await?;
+        // The user has paid, so a lagging collection must not fail the purchase
+        let (purchase_token, times, receipt) = match purchased_sku {
+            Some(sku) => sku,
+            None => {
+                log::warn!(
+                    "The user's collection does not report the purchase of {} yet, returning a provisional token",
+                    payload.product_id
+                );
+                self.provisional_sku(&context, &payload.product_id).await?
+            }
+        };
+        let receipt = WindowsReceipt {
+            product_id: product.product_id.clone(),
+            status: purchase_status(status),
+            extended_error: purchase_result
+                .ExtendedError()
+                .ok()
+                .and_then(extended_error),
+            ..receipt
         };
 
-        // Generate purchase details
-        let purchase_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| {
-                crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
-                    code: Some("systemTimeError".to_string()),
-                    message: Some(format!("Failed to get system time: {:?}", e)),
-                    data: (),
-                }))
-            })?
-            .as_millis() as i64;
-
-        let purchase_token = format!("win_{}_{}", product.product_id, purchase_time);
+        // A purchase that just succeeded was made now, even if the
+        // collection has not caught up yet
+        let purchase_time = times
+            .purchase_time
+            .or_else(|| (status == StorePurchaseStatus::Succeeded).then(crate::time::now_millis));
 
         let purchase = Purchase {
             order_id: Some(purchase_token.clone()),
             package_name: product_title,
             product_id: product.product_id.clone(),
             purchase_time,
+            expiration_time: times.expiration_time,
+            is_trial: times.is_trial,
             purchase_token: purchase_token.clone(),
             purchase_state,
             is_auto_renewing: product.product_type == "subs",
             is_acknowledged: true, // Windows Store handles acknowledgment
-            original_json: format!(
-                r#"{{"status":{},"message":"{}","productId":"{}"}}"#,
-                status.0, error_message, product.product_id
-            ),
+            original_json: receipt_json(&receipt)?,
             signature: String::new(), // Windows doesn't provide signatures like Android
             original_id: None, // Windows doesn't have original transaction IDs like iOS/macOS
             jws_representation: None, // Windows doesn't have JWS like iOS/macOS
+            revocation_time: None,
+            revocation_reason: None,
+            account_token: None,
+            ownership_type: OwnershipType::Purchased,
+            environment: StoreEnvironment::Production,
+            transaction_id: None,
+            web_order_line_item_id: None,
+            quantity: 1,
         };
 
         // Emit event for purchase state change
@@ -418,9 +682,8 @@ impl<R: Runtime> Iap<R> {
         let context = self.get_store_context()?;
 
         // Get app license info
-        let app_license = context
-            .GetAppLicenseAsync()
-            .and_then(|async_op| async_op.get())?;
+        let app_license = self.request(context.GetAppLicenseAsync()).await?;
+        let collection = self.user_collection(&context).await?;
 
         let mut purchases = Vec::new();
 
@@ -432,7 +695,8 @@ impl<R: Runtime> Iap<R> {
             let item = iterator.Current()?;
             let license = item.Value()?;
 
-            let purchase = self.convert_license_to_purchase(&license, &product_type)?;
+            let purchase =
+                self.convert_license_to_purchase(&license, &product_type, &collection)?;
 
             if purchase.purchase_state == PurchaseStateValue::Purchased {
                 purchases.push(purchase);
@@ -448,32 +712,18 @@ impl<R: Runtime> Iap<R> {
         &self,
         license: &StoreLicense,
         product_type: &str,
+        collection: &HashMap<String, StoreCollectionData>,
     ) -> crate::Result<Purchase> {
         let product_id = license.InAppOfferToken()?.to_string();
 
         let sku_store_id = license.SkuStoreId()?.to_string();
+        let data = collection.get(&sku_store_id);
+        let purchase_token = purchase_token(&sku_store_id, Self::acquired_millis(data));
+        let times = Self::entitlement_times(data, Some(license));
+        let receipt = Self::receipt(&product_id, Some(&sku_store_id), data, Some(license))?;
 
         let is_active = license.IsActive()?;
 
-        let expiration_date = license.ExpirationDate()?;
-        let expiration_millis = Self::datetime_to_unix_millis(&expiration_date);
-
-        // Estimate purchase time (30 days before expiration for monthly subs)
-        let purchase_time = if product_type == "subs" && expiration_millis > 0 {
-            expiration_millis - (30 * 24 * 60 * 60 * 1000)
-        } else {
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map_err(|e| {
-                    crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
-                        code: Some("systemTimeError".to_string()),
-                        message: Some(format!("Failed to get system time: {:?}", e)),
-                        data: (),
-                    }))
-                })?
-                .as_millis() as i64
-        };
-
         let purchase_state = if is_active {
             PurchaseStateValue::Purchased
         } else {
@@ -481,24 +731,154 @@ impl<R: Runtime> Iap<R> {
         };
 
         Ok(Purchase {
-            order_id: Some(sku_store_id.clone()),
+            order_id: Some(purchase_token.clone()),
             package_name: self.app_handle.package_info().name.clone(),
             product_id,
-            purchase_time,
-            purchase_token: sku_store_id,
+            purchase_time: times.purchase_time,
+            expiration_time: times.expiration_time,
+            is_trial: times.is_trial,
+            purchase_token: purchase_token.clone(),
             purchase_state,
             is_auto_renewing: product_type == "subs" && is_active,
             is_acknowledged: true,
-            original_json: format!(
-                r#"{{"isActive":{},"expirationDate":{}}}"#,
-                is_active, expiration_millis
-            ),
+            original_json: receipt_json(&receipt)?,
             signature: String::new(),
             original_id: None,
             jws_representation: None, // Windows doesn't have JWS like iOS/macOS
+            revocation_time: None,
+            revocation_reason: None,
+            account_token: None,
+            ownership_type: OwnershipType::Purchased,
+            environment: StoreEnvironment::Production,
+            transaction_id: None,
+            web_order_line_item_id: None,
+            quantity: 1,
         })
     }
 
+    /// SKUs in the user's collection, keyed by SKU Store ID.
+    async fn user_collection(
+        &self,
+        context: &StoreContext,
+    ) -> crate::Result<HashMap<String, StoreCollectionData>> {
+        let product_kinds: IIterable<HSTRING> = vec![
+            HSTRING::from("Consumable"),
+            HSTRING::from("UnmanagedConsumable"),
+            HSTRING::from("Durable"),
+            HSTRING::from("Subscription"),
+        ]
+        .into();
+
+        let query_result = self
+            .request(context.GetUserCollectionAsync(&product_kinds))
+            .await?;
+
+        let extended_error = query_result.ExtendedError()?;
+        if extended_error.is_err() {
+            return Err(crate::Error::PluginInvoke(
+                PluginInvokeError::InvokeRejected(ErrorResponse {
+                    code: Some("storeQueryFailed".to_string()),
+                    message: Some(format!(
+                        "User collection query failed with error: {:?}",
+                        extended_error.message()
+                    )),
+                    data: (),
+                }),
+            ));
+        }
+
+        let mut collection = HashMap::new();
+
+        let iterator = query_result.Products()?.First()?;
+        while iterator.HasCurrent()? {
+            let skus = iterator.Current()?.Value()?.Skus()?;
+            for i in 0..skus.Size()? {
+                let sku = skus.GetAt(i)?;
+                if sku.IsInUserCollection()? {
+                    collection.insert(sku.StoreId()?.to_string(), sku.CollectionData()?);
+                }
+            }
+
+            iterator.MoveNext()?;
+        }
+
+        Ok(collection)
+    }
+
+    /// Token, entitlement times and receipt of the user's purchase of the
+    /// add-on `store_id`, derived the same way as in `restore_purchases` and
+    /// `get_product_status`.
+    ///
+    /// The collection may lag behind a purchase that just completed, so it is
+    /// polled until it reports an entry other than `previous_token`, the
+    /// add-on's token before the purchase. Returns `None` if it never does.
+    async fn purchased_sku(
+        &self,
+        context: &StoreContext,
+        store_id: &str,
+        previous_token: Option<&str>,
+    ) -> crate::Result<Option<(String, EntitlementTimes, WindowsReceipt)>> {
+        for attempt in 0..COLLECTION_POLL_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(COLLECTION_POLL_INTERVAL).await;
+            }
+
+            let collection = match self.user_collection(context).await {
+                Ok(collection) => collection,
+                Err(e) => {
+                    log::debug!("Failed to query the user's collection: {e}");
+                    continue;
+                }
+            };
+            let Some((sku_store_id, data)) = collection_entry(&collection, store_id) else {
+                continue;
+            };
+            let token = purchase_token(sku_store_id, Self::acquired_millis(Some(data)));
+            if previous_token != Some(token.as_str()) {
+                return Ok(Some((
+                    token,
+                    Self::entitlement_times(Some(data), None),
+                    Self::receipt(store_id, Some(sku_store_id), Some(data), None)?,
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Provisional token, entitlement times and receipt of a purchase the
+    /// user's collection does not report yet, taken from the add-on's license
+    /// if the Store has issued one.
+    ///
+    /// The token has no acquisition time, so `consume_purchase` rejects it
+    /// until `restore_purchases` reports the purchase with its final token.
+    async fn provisional_sku(
+        &self,
+        context: &StoreContext,
+        store_id: &str,
+    ) -> crate::Result<(String, EntitlementTimes, WindowsReceipt)> {
+        let license = match self.request(context.GetAppLicenseAsync()).await {
+            Ok(app_license) => app_license
+                .AddOnLicenses()?
+                .Lookup(&HSTRING::from(store_id))
+                .ok(),
+            Err(e) => {
+                log::debug!("Failed to get the app license: {e}");
+                None
+            }
+        };
+        let sku_store_id = license
+            .as_ref()
+            .and_then(|license| license.SkuStoreId().ok())
+            .map(|id| id.to_string());
+
+        Ok((
+            purchase_token(sku_store_id.as_deref().unwrap_or(store_id), None),
+            Self::entitlement_times(None, license.as_ref()),
+            Self::receipt(store_id, sku_store_id.as_deref(), None, license.as_ref())?,
+        ))
+    }
+
     pub async fn acknowledge_purchase(
         &self,
         _purchase_token: String,
@@ -516,9 +896,7 @@ impl<R: Runtime> Iap<R> {
         let context = self.get_store_context()?;
 
         // Get app license to check ownership
-        let app_license = context
-            .GetAppLicenseAsync()
-            .and_then(|async_op| async_op.get())?;
+        let app_license = self.request(context.GetAppLicenseAsync()).await?;
 
         let addon_licenses = app_license.AddOnLicenses()?;
 
@@ -530,14 +908,6 @@ impl<R: Runtime> Iap<R> {
             let license = addon_licenses.Lookup(&product_key)?;
 
             let is_active = license.IsActive()?;
-            let expiration_date = license.ExpirationDate()?;
-            let expiration_time = Self::datetime_to_unix_millis(&expiration_date);
-
-            let purchase_time = if product_type == "subs" && expiration_time > 0 {
-                expiration_time - (30 * 24 * 60 * 60 * 1000)
-            } else {
-                expiration_time
-            };
 
             let purchase_state = if is_active {
                 Some(PurchaseStateValue::Purchased)
@@ -546,20 +916,22 @@ impl<R: Runtime> Iap<R> {
             };
 
             let sku_store_id = license.SkuStoreId()?.to_string();
+            let collection = self.user_collection(&context).await?;
+            let data = collection.get(&sku_store_id);
+            let purchase_token = purchase_token(&sku_store_id, Self::acquired_millis(data));
+            let times = Self::entitlement_times(data, Some(&license));
 
             Ok(ProductStatus {
                 product_id,
                 is_owned: is_active,
                 purchase_state,
-                purchase_time: Some(purchase_time),
-                expiration_time: if expiration_time > 0 {
-                    Some(expiration_time)
-                } else {
-                    None
-                },
+                purchase_time: times.purchase_time,
+                expiration_time: times.expiration_time,
+                is_trial: times.is_trial,
                 is_auto_renewing: Some(product_type == "subs" && is_active),
                 is_acknowledged: Some(true),
-                purchase_token: Some(sku_store_id),
+                purchase_token: Some(purchase_token),
+                ownership_type: Some(OwnershipType::Purchased),
             })
         } else {
             Ok(ProductStatus {
@@ -568,27 +940,460 @@ impl<R: Runtime> Iap<R> {
                 purchase_state: None,
                 purchase_time: None,
                 expiration_time: None,
+                is_trial: None,
                 is_auto_renewing: None,
                 is_acknowledged: None,
                 purchase_token: None,
+                ownership_type: None,
             })
         }
     }
 
     pub async fn consume_purchase(
         &self,
-        _purchase_token: String,
+        purchase_token: String,
+        quantity: u32,
     ) -> crate::Result<ConsumePurchaseResponse> {
-        // Windows Store handles consumable products automatically
-        // This method exists for API compatibility
-        Ok(ConsumePurchaseResponse { success: true })
+        let store_id = product_store_id(&purchase_token).ok_or_else(|| {
+            crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
+                code: Some("invalidPurchaseToken".to_string()),
+                message: Some(format!("Invalid purchase token: {purchase_token}")),
+                data: (),
+            }))
+        })?;
+        if is_provisional_token(&purchase_token) {
+            return Err(crate::Error::PluginInvoke(
+                PluginInvokeError::InvokeRejected(ErrorResponse {
+                    code: Some("provisionalPurchaseToken".to_string()),
+                    message: Some(format!(
+                        "Purchase token {purchase_token} is provisional. Call restorePurchases to get the purchase's final token before consuming it."
+                    )),
+                    data: (),
+                }),
+            ));
+        }
+
+        let context = self.get_store_context()?;
+
+        // Retries reuse the tracking id, so the Store fulfills a purchase once
+        let tracking_id = GUID::from_u128(fulfillment_tracking_id(&purchase_token).as_u128());
+        let result = self
+            .request(context.ReportConsumableFulfillmentAsync(
+                &HSTRING::from(store_id),
+                quantity,
+                tracking_id,
+            ))
+            .await?;
+
+        Ok(ConsumePurchaseResponse {
+            success: true,
+            balance_remaining: Some(Self::consumable_balance(&result)?),
+        })
+    }
+
+    pub async fn get_consumable_balance_remaining(
+        &self,
+        product_id: String,
+    ) -> crate::Result<ConsumableBalance> {
+        let context = self.get_store_context()?;
+
+        let result = self
+            .request(context.GetConsumableBalanceRemainingAsync(&HSTRING::from(&product_id)))
+            .await?;
+
+        Ok(ConsumableBalance {
+            balance_remaining: Self::consumable_balance(&result)?,
+            product_id,
+        })
+    }
+
+    /// Remaining balance of a successful consumable request, or its status as an error.
+    fn consumable_balance(result: &StoreConsumableResult) -> crate::Result<u32> {
+        let status = result.Status()?;
+
+        let (code, message) = match status {
+            StoreConsumableStatus::Succeeded => return Ok(result.BalanceRemaining()?),
+            StoreConsumableStatus::InsufficentQuantity => (
+                "insufficientQuantity",
+                format!(
+                    "Not enough units remaining, balance is {}",
+                    result.BalanceRemaining()?
+                ),
+            ),
+            StoreConsumableStatus::NetworkError => (
+                "networkError",
+                "Network error during consumable fulfillment".to_string(),
+            ),
+            StoreConsumableStatus::ServerError => (
+                "serverError",
+                "Server error during consumable fulfillment".to_string(),
+            ),
+            _ => (
+                "consumeFailed",
+                format!("Consumable fulfillment failed with status {}", status.0),
+            ),
+        };
+
+        let message = match result.ExtendedError() {
+            Ok(error) if error.is_err() => format!("{message}: {}", error.message()),
+            _ => message,
+        };
+
+        Err(crate::Error::PluginInvoke(
+            PluginInvokeError::InvokeRejected(ErrorResponse {
+                code: Some(code.to_string()),
+                message: Some(message),
+                data: (),
+            }),
+        ))
+    }
+
+    pub async fn get_app_license(&self) -> crate::Result<AppLicense> {
+        let context = self.get_store_context()?;
+        let license = self.request(context.GetAppLicenseAsync()).await?;
+
+        let is_trial = license.IsTrial()?;
+        let trial_unique_id = license.TrialUniqueId()?;
+
+        Ok(AppLicense {
+            is_applicable: true,
+            is_active: Some(license.IsActive()?),
+            is_trial: Some(is_trial),
+            // TimeSpan is in 100-nanosecond intervals
+            trial_time_remaining_secs: is_trial
+                .then(|| license.TrialTimeRemaining())
+                .transpose()?
+                .map(|remaining| remaining.Duration / 10_000_000),
+            trial_unique_id: (!trial_unique_id.is_empty()).then(|| trial_unique_id.to_string()),
+            expiration_time: known_millis(Self::datetime_to_unix_millis(
+                &license.ExpirationDate()?,
+            )),
+            ..AppLicense::not_applicable()
+        })
+    }
+
+    pub async fn get_app_transaction(&self, _refresh: bool) -> crate::Result<AppTransaction> {
+        Err(crate::Error::from(std::io::Error::other(
+            "App transactions are only available on iOS and macOS",
+        )))
+    }
+
+    pub async fn begin_refund_request(
+        &self,
+        _transaction_id: String,
+    ) -> crate::Result<RefundRequestResponse> {
+        Err(crate::Error::from(std::io::Error::other(
+            "Refund requests are only available on iOS and macOS",
+        )))
+    }
+
+    /// Opens the add-on's Store page, where its subscription is managed.
+    pub async fn show_manage_subscriptions(&self, product_id: Option<String>) -> crate::Result<()> {
+        let uri = crate::links::microsoft_store_subscriptions_uri(
+            product_id.as_deref().and_then(product_store_id),
+        );
+
+        if self
+            .request(Launcher::LaunchUriAsync(&Uri::CreateUri(&HSTRING::from(
+                &uri,
+            ))?))
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(crate::Error::PluginInvoke(
+                PluginInvokeError::InvokeRejected(ErrorResponse {
+                    code: Some("launchFailed".to_string()),
+                    message: Some(format!("Failed to open {uri}")),
+                    data: (),
+                }),
+            ))
+        }
+    }
+
+    pub async fn present_code_redemption(&self) -> crate::Result<()> {
+        Err(crate::Error::from(std::io::Error::other(
+            "Code redemption is only available on iOS, macOS and Android",
+        )))
+    }
+
+    pub async fn get_pending_purchases(&self) -> crate::Result<GetPendingPurchasesResponse> {
+        // Microsoft Store purchases complete or fail within the purchase dialog
+        Ok(GetPendingPurchasesResponse {
+            pending_purchases: Vec::new(),
+        })
+    }
+
+    pub async fn get_storefront(&self) -> crate::Result<Storefront> {
+        let context = self.get_store_context()?;
+
+        // The app's own listing is served for, and priced in, the user's market
+        let app_product = self
+            .request(context.GetStoreProductForCurrentAppAsync())
+            .await?
+            .Product();
+        let currency_code = app_product
+            .as_ref()
+            .ok()
+            .and_then(|product| product.Price().ok())
+            .and_then(|price| price.CurrencyCode().ok())
+            .map(|code| code.to_string())
+            .filter(|code| !code.is_empty());
+
+        let market = app_product
+            .as_ref()
+            .ok()
+            .and_then(|product| product.ExtendedJsonData().ok())
+            .and_then(|json| store_market(&json.to_string()));
+        let region_code = match market {
+            Some(market) => HSTRING::from(market),
+            None => {
+                // Apps without a Store listing, e.g. sideloaded builds, have no market
+                log::debug!("Store market unavailable, using the Windows region setting");
+                GlobalizationPreferences::HomeGeographicRegion()?
+            }
+        };
+        let country_code = GeographicRegion::CreateGeographicRegion(&region_code)?
+            .CodeThreeLetter()?
+            .to_string();
+
+        let storefront = Storefront {
+            country_code,
+            id: None,
+            currency_code,
+        };
+        self.update_storefront(&storefront);
+
+        Ok(storefront)
+    }
+
+    pub async fn capabilities(&self) -> crate::Result<Capabilities> {
+        let prerequisite_error = self.get_store_context().err().map(|e| e.to_string());
+        let prerequisites_met = prerequisite_error.is_none();
+
+        Ok(Capabilities {
+            backend: StoreBackend::MicrosoftStore,
+            supported_operations: SUPPORTED_OPERATIONS.to_vec(),
+            prerequisites_met,
+            prerequisite_error,
+            // The Store doesn't report payment restrictions up front
+            can_make_payments: prerequisites_met,
+        })
+    }
+
+    /// Emit `storefrontChanged` if the storefront differs from the last one
+    /// seen. The Store has no change notification, so this is the only source.
+    fn update_storefront(&self, storefront: &Storefront) {
+        let Ok(mut last) = self.storefront.lock() else {
+            return;
+        };
+        let changed = last.as_ref().is_some_and(|previous| previous != storefront);
+        *last = Some(storefront.clone());
+        drop(last);
+
+        if changed {
+            self.trigger("storefrontChanged", storefront);
+        }
+    }
+}
+
+/// Awaits a WinRT async operation without blocking the async runtime.
+///
+/// The operation is cancelled if it does not complete within `timeout`, or if
+/// the returned future is dropped first.
+async fn await_operation<T: RuntimeType + 'static>(
+    operation: IAsyncOperation<T>,
+    timeout: Option<Duration>,
+) -> crate::Result<T> {
+    let guard = CancelOnDrop(Some(operation.clone()));
+
+    let result = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, operation.into_future())
+            .await
+            .map_err(|_| {
+                crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
+                    code: Some("timeout".to_string()),
+                    message: Some(format!(
+                        "Store request timed out after {}s",
+                        timeout.as_secs()
+                    )),
+                    data: (),
+                }))
+            })?,
+        None => operation.await,
+    };
+
+    guard.disarm();
+    Ok(result?)
+}
+
+/// Cancels a pending WinRT async operation when dropped, unless disarmed.
+struct CancelOnDrop<T: RuntimeType + 'static>(Option<IAsyncOperation<T>>);
+
+impl<T: RuntimeType + 'static> CancelOnDrop<T> {
+    fn disarm(mut self) {
+        self.0 = None;
     }
 }
 
+impl<T: RuntimeType + 'static> Drop for CancelOnDrop<T> {
+    fn drop(&mut self) {
+        if let Some(operation) = self.0.take() {
+            let _ = operation.Cancel();
+        }
+    }
+}
+
+/// Purchase and expiration times of an entitlement, `None` where unknown.
+#[derive(Debug, Default)]
+struct EntitlementTimes {
+    purchase_time: Option<i64>,
+    expiration_time: Option<i64>,
+    is_trial: Option<bool>,
+}
+
+/// Unix milliseconds from 9999-01-01, which the Store reports as the
+/// expiration of perpetual licenses.
+const PERPETUAL_MILLIS: i64 = 253_370_764_800_000;
+
+/// A Store date in Unix milliseconds, or `None` for unset (zero) dates and
+/// the far-future expiration of perpetual licenses.
+fn known_millis(unix_millis: i64) -> Option<i64> {
+    (unix_millis > 0 && unix_millis < PERPETUAL_MILLIS).then_some(unix_millis)
+}
+
+/// Serializes a receipt for `Purchase::original_json`.
+fn receipt_json(receipt: &WindowsReceipt) -> crate::Result<String> {
+    serde_json::to_string(receipt).map_err(|e| PluginInvokeError::CannotSerializePayload(e).into())
+}
+
+fn purchase_status(status: StorePurchaseStatus) -> Option<WindowsPurchaseStatus> {
+    match status {
+        StorePurchaseStatus::Succeeded => Some(WindowsPurchaseStatus::Succeeded),
+        StorePurchaseStatus::AlreadyPurchased => Some(WindowsPurchaseStatus::AlreadyPurchased),
+        StorePurchaseStatus::NotPurchased => Some(WindowsPurchaseStatus::NotPurchased),
+        StorePurchaseStatus::NetworkError => Some(WindowsPurchaseStatus::NetworkError),
+        StorePurchaseStatus::ServerError => Some(WindowsPurchaseStatus::ServerError),
+        _ => None,
+    }
+}
+
+/// The extended error of a Store result, if it reports a failure.
+fn extended_error(error: HRESULT) -> Option<WindowsExtendedError> {
+    error.is_err().then(|| WindowsExtendedError {
+        code: error.0,
+        message: error.message(),
+    })
+}
+
+/// Stable purchase token for a SKU: `<storeId>/<skuId>@<acquired ms>`.
+///
+/// The acquisition time tells repeat purchases of a consumable apart, so
+/// their fulfillment tracking ids differ. It is omitted from the provisional
+/// token of a purchase the user's collection does not report yet.
+fn purchase_token(sku_store_id: &str, acquired_millis: Option<i64>) -> String {
+    match acquired_millis {
+        Some(millis) => format!("{sku_store_id}@{millis}"),
+        None => sku_store_id.to_string(),
+    }
+}
+
+/// Whether a purchase token lacks the acquisition time, i.e. was issued before
+/// the user's collection reported the purchase. Its fulfillment tracking id
+/// would differ from the final token's, so consuming it could count the
+/// fulfillment twice. Tokens of earlier versions are not provisional.
+fn is_provisional_token(purchase_token: &str) -> bool {
+    !purchase_token.starts_with("win_") && !purchase_token.contains('@')
+}
+
+/// Product Store ID of the add-on a purchase token refers to.
+///
+/// Also accepts the `win_<storeId>_<timestamp>` tokens of earlier versions.
+fn product_store_id(purchase_token: &str) -> Option<&str> {
+    let store_id = match purchase_token.strip_prefix("win_") {
+        Some(rest) => rest.rsplit_once('_')?.0,
+        None => purchase_token.split('/').next()?,
+    };
+    (!store_id.is_empty()).then_some(store_id)
+}
+
+/// Collection entry of the add-on `store_id`, the first SKU by Store ID if
+/// the user owns several.
+fn collection_entry<'a>(
+    collection: &'a HashMap<String, StoreCollectionData>,
+    store_id: &str,
+) -> Option<(&'a String, &'a StoreCollectionData)> {
+    collection
+        .iter()
+        .filter(|(sku_store_id, _)| product_store_id(sku_store_id) == Some(store_id))
+        .min_by(|(a, _), (b, _)| a.cmp(b))
+}
+
+/// Tracking id reported with a consumable fulfillment, derived from the
+/// purchase token so that consuming the same purchase twice is a no-op.
+fn fulfillment_tracking_id(purchase_token: &str) -> uuid::Uuid {
+    uuid::Uuid::new_v5(&FULFILLMENT_NAMESPACE, purchase_token.as_bytes())
+}
+
+/// Two-letter market a Store listing was served for, from its extended JSON.
+///
+/// Listings queried by the app are scoped to the user's market, so their
+/// availabilities name exactly one market. Lists of several markets describe
+/// where the product is sold, not where the user is, and are ignored.
+fn store_market(extended_json: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(extended_json).ok()?;
+    let availabilities = json
+        .get("DisplaySkuAvailabilities")?
+        .as_array()?
+        .iter()
+        .filter_map(|sku| sku.get("Availabilities")?.as_array())
+        .flatten();
+    availabilities
+        .filter_map(|availability| availability.get("Markets")?.as_array())
+        .find_map(|markets| match markets.as_slice() {
+            [market] => market.as_str().map(str::to_string),
+            _ => None,
+        })
+        .filter(|market| market.len() == 2)
+}
+
+/// Picks the window Store UI is attached to: the configured label, else the
+/// window that last invoked a command, else `main`, else any open window.
+///
+/// A configured label never falls back, so a typo is reported instead of
+/// silently attaching to another window.
+fn select_window_label<'a>(
+    configured: Option<&'a str>,
+    invoking: Option<&'a str>,
+    open: &[&'a str],
+) -> Option<&'a str> {
+    if let Some(label) = configured {
+        return open.contains(&label).then_some(label);
+    }
+    invoking
+        .filter(|label| open.contains(label))
+        .or_else(|| open.iter().copied().find(|label| *label == "main"))
+        .or_else(|| open.iter().copied().min())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_store_market() {
+        let json = r#"{"ProductId":"9NBLGGH4TNNR","DisplaySkuAvailabilities":[{"Sku":{"SkuId":"0010"},"Availabilities":[{"AvailabilityId":"9RJ1","Markets":["DE"]}]}]}"#;
+        assert_eq!(store_market(json).as_deref(), Some("DE"));
+    }
+
+    #[test]
+    fn test_store_market_ignores_market_lists() {
+        let json = r#"{"DisplaySkuAvailabilities":[{"Availabilities":[{"Markets":["US","DE"]}]}]}"#;
+        assert_eq!(store_market(json), None);
+        assert_eq!(store_market("{}"), None);
+        assert_eq!(store_market("not json"), None);
+    }
+
     #[test]
     fn test_datetime_to_unix_millis_epoch() {
         // Unix epoch: January 1, 1970 00:00:00 UTC
@@ -658,6 +1463,31 @@ mod tests {
         assert_eq!(result, 1000);
     }
 
+    #[test]
+    fn test_convert_billing_period() {
+        let cases = [
+            (StoreDurationUnit::Day, PeriodUnit::Day),
+            (StoreDurationUnit::Week, PeriodUnit::Week),
+            (StoreDurationUnit::Month, PeriodUnit::Month),
+            (StoreDurationUnit::Year, PeriodUnit::Year),
+        ];
+        for (store_unit, unit) in cases {
+            let period = Iap::<tauri::Wry>::convert_billing_period(3, store_unit)
+                .expect("Failed to convert billing period");
+            assert_eq!(
+                period,
+                BillingPeriod::new(3, unit).expect("Invalid billing period")
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_billing_period_rejects_unsupported() {
+        assert!(Iap::<tauri::Wry>::convert_billing_period(1, StoreDurationUnit::Hour).is_err());
+        assert!(Iap::<tauri::Wry>::convert_billing_period(1, StoreDurationUnit::Minute).is_err());
+        assert!(Iap::<tauri::Wry>::convert_billing_period(0, StoreDurationUnit::Month).is_err());
+    }
+
     #[test]
     fn test_datetime_to_unix_millis_far_future() {
         // January 1, 2100 00:00:00 UTC
@@ -669,4 +1499,99 @@ mod tests {
         // Should be approximately 4102444800000 ms
         assert!(result > 4000000000000);
     }
+
+    #[test]
+    fn test_select_window_label_configured() {
+        let open = ["main", "store"];
+        assert_eq!(
+            select_window_label(Some("store"), Some("main"), &open),
+            Some("store")
+        );
+        assert_eq!(
+            select_window_label(Some("missing"), Some("main"), &open),
+            None
+        );
+    }
+
+    #[test]
+    fn test_select_window_label_prefers_invoking_window() {
+        let open = ["main", "settings"];
+        assert_eq!(
+            select_window_label(None, Some("settings"), &open),
+            Some("settings")
+        );
+    }
+
+    #[test]
+    fn test_select_window_label_falls_back() {
+        // The invoking window was closed
+        assert_eq!(
+            select_window_label(None, Some("closed"), &["main", "other"]),
+            Some("main")
+        );
+        assert_eq!(select_window_label(None, None, &["b", "a"]), Some("a"));
+        assert_eq!(select_window_label(None, None, &[]), None);
+    }
+
+    #[test]
+    fn test_product_store_id() {
+        assert_eq!(
+            product_store_id("win_9NBLGGH4TNNR_1700000000000"),
+            Some("9NBLGGH4TNNR")
+        );
+        assert_eq!(product_store_id("9NBLGGH4TNNR/0010"), Some("9NBLGGH4TNNR"));
+        assert_eq!(
+            product_store_id("9NBLGGH4TNNR/0010@1700000000000"),
+            Some("9NBLGGH4TNNR")
+        );
+        assert_eq!(product_store_id("9NBLGGH4TNNR"), Some("9NBLGGH4TNNR"));
+        assert_eq!(product_store_id("win_"), None);
+        assert_eq!(product_store_id(""), None);
+    }
+
+    #[test]
+    fn test_fulfillment_tracking_id_is_stable_per_token() {
+        let first = fulfillment_tracking_id("9NBLGGH4TNNR/0010");
+        assert_eq!(first, fulfillment_tracking_id("9NBLGGH4TNNR/0010"));
+        assert_ne!(first, fulfillment_tracking_id("9NBLGGH4TNNR/0011"));
+        assert_eq!(first.get_version_num(), 5);
+    }
+
+    #[test]
+    fn test_purchase_token() {
+        assert_eq!(
+            purchase_token("9NBLGGH4TNNR/0010", Some(1_700_000_000_000)),
+            "9NBLGGH4TNNR/0010@1700000000000"
+        );
+        assert_eq!(
+            purchase_token("9NBLGGH4TNNR/0010", None),
+            "9NBLGGH4TNNR/0010"
+        );
+    }
+
+    #[test]
+    fn test_is_provisional_token() {
+        assert!(is_provisional_token("9NBLGGH4TNNR/0010"));
+        assert!(is_provisional_token("9NBLGGH4TNNR"));
+        assert!(!is_provisional_token("9NBLGGH4TNNR/0010@1700000000000"));
+        assert!(!is_provisional_token("win_9NBLGGH4TNNR_1700000000000"));
+    }
+
+    #[test]
+    fn test_known_millis() {
+        assert_eq!(known_millis(1_700_000_000_000), Some(1_700_000_000_000));
+        // Unset dates and the expiration of perpetual licenses are unknown
+        assert_eq!(known_millis(0), None);
+        assert_eq!(known_millis(-11_644_473_600_000), None);
+        assert_eq!(known_millis(253_402_300_799_000), None);
+    }
+
+    #[test]
+    fn test_extended_error() {
+        assert_eq!(extended_error(HRESULT(0)), None);
+
+        let error = extended_error(HRESULT(0x800704C7_u32 as i32))
+            .expect("Failed to map a failing HRESULT");
+        assert_eq!(error.code, -2147023673);
+    }
 }